@@ -0,0 +1,18 @@
+/// Formats a duration in milliseconds as minutes and seconds, as shown on the game clocks.
+pub fn format_clock(milliseconds: u64) -> String {
+    let seconds = milliseconds / 1000;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_clock;
+
+    #[test]
+    fn clock_shows_whole_minutes_and_seconds() {
+        assert_eq!(format_clock(0), "0:00");
+        assert_eq!(format_clock(30 * 1000), "0:30");
+        assert_eq!(format_clock(90 * 1000 + 999), "1:30");
+        assert_eq!(format_clock(30 * 60 * 1000), "30:00");
+    }
+}
@@ -0,0 +1,261 @@
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use serde::Deserialize;
+
+use crate::{official::{Official, OfficialRole}, random_id, team_side::TeamSide};
+
+/// The minimum number of officials in each role for a sanctioned game.
+pub const REQUIRED_ROLES: &[(OfficialRole, usize)] = &[
+    (OfficialRole::InsidePackReferee, 1),
+    (OfficialRole::JammerReferee, 2),
+    (OfficialRole::JamTimer, 1),
+    (OfficialRole::Scorekeeper, 2),
+    (OfficialRole::ScoreboardOperator, 1),
+    (OfficialRole::PenaltyLineupTracker, 1),
+    (OfficialRole::PenaltyBoxTimer, 1),
+];
+pub const MIN_REFEREE_COUNT: usize = 5;
+
+/// An officiating crew as read from a JSON or CSV file.
+///
+/// JSON files hold an `officials` list. CSV files hold one official per row with a header of
+/// `name,role` plus the optional `head`, `league`, `certification` and `team` columns. Officials in
+/// team-specific roles without a `team` are split evenly between the teams.
+#[derive(Clone, Debug, Deserialize)]
+pub struct CrewFile {
+    pub officials: Vec<CrewOfficial>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CrewOfficial {
+    pub name: String,
+    pub role: String,
+    #[serde(default)]
+    pub head: bool,
+    pub league: Option<String>,
+    pub certification: Option<String>,
+    /// The team covered by a team-specific role, `home` or `away`.
+    pub team: Option<String>,
+}
+
+impl CrewFile {
+    /// Reads a crew from a `.json` or `.csv` file.
+    pub fn read(path: &Path) -> Result<CrewFile, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+
+        match extension.as_str() {
+            "json" => serde_json::from_str(&contents).map_err(|e| e.to_string()),
+            "csv" => Ok(CrewFile { officials: Self::read_csv_officials(&contents)? }),
+            _ => Err(format!("Unsupported crew file type '{}', expected .json or .csv", extension)),
+        }
+    }
+
+    fn read_csv_officials(contents: &str) -> Result<Vec<CrewOfficial>, String> {
+        csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(contents.as_bytes())
+            .deserialize()
+            .map(|r| r.map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    /// Checks that the crew covers the roles needed for a sanctioned game, with one head referee and one head NSO.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut roles = Vec::new();
+        for official in self.officials.iter() {
+            if official.name.trim().is_empty() {
+                return Err("Official name must not be empty".to_string());
+            }
+
+            let role: OfficialRole = official.role.parse().map_err(|e| format!("{}: {}", official.name, e))?;
+            roles.push((role, official.head));
+
+            if let Some(team) = &official.team {
+                Self::parse_team(team).map_err(|e| format!("{}: {}", official.name, e))?;
+
+                if !role.is_team_assigned() {
+                    return Err(format!("{}: {:?} officials are not assigned to a team", official.name, role));
+                }
+            }
+        }
+
+        for (required_role, required_count) in REQUIRED_ROLES {
+            let count = roles.iter().filter(|(r, _)| r == required_role).count();
+            if count < *required_count {
+                return Err(format!("Crew has {} {:?} officials, at least {} required", count, required_role, required_count));
+            }
+        }
+
+        let referee_count = roles.iter().filter(|(r, _)| r.is_referee() && *r != OfficialRole::RefereeAlternate).count();
+        if referee_count < MIN_REFEREE_COUNT {
+            return Err(format!("Crew has {} referees, at least {} required", referee_count, MIN_REFEREE_COUNT));
+        }
+
+        let head_referee_count = roles.iter().filter(|(r, h)| *h && r.is_referee()).count();
+        if head_referee_count != 1 {
+            return Err(format!("Crew has {} head referees, exactly 1 required", head_referee_count));
+        }
+
+        let head_nso_count = roles.iter().filter(|(r, h)| *h && !r.is_referee()).count();
+        if head_nso_count != 1 {
+            return Err(format!("Crew has {} head NSOs, exactly 1 required", head_nso_count));
+        }
+
+        Ok(())
+    }
+
+    /// Validates the crew and builds the officials from it.
+    pub fn into_crew(self, random_source: &mut StdRng) -> Result<Vec<Official>, String> {
+        self.validate()?;
+
+        let mut crew: Vec<Official> = self.officials.into_iter().map(|o| Official {
+            id: random_id::random_uuid(random_source),
+            name: o.name.trim().to_string(),
+            is_head: o.head,
+            role: o.role.parse().unwrap(),
+            league: o.league,
+            certification: o.certification,
+            team: o.team.map(|t| Self::parse_team(&t).unwrap()),
+        }).collect();
+
+        for index in 0..crew.len() {
+            let role = crew[index].role;
+            if !role.is_team_assigned() || crew[index].team.is_some() {
+                continue;
+            }
+
+            let covering = |team: TeamSide| crew.iter().filter(|o| o.role == role && o.team == Some(team)).count();
+            crew[index].team = Some(if covering(TeamSide::Away) < covering(TeamSide::Home) { TeamSide::Away } else { TeamSide::Home });
+        }
+
+        Ok(crew)
+    }
+
+    fn parse_team(team: &str) -> Result<TeamSide, String> {
+        match team.trim().to_lowercase().as_str() {
+            "home" | "1" => Ok(TeamSide::Home),
+            "away" | "2" => Ok(TeamSide::Away),
+            _ => Err(format!("Unknown team '{}', expected home or away", team)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CrewFile, CrewOfficial};
+    use crate::{official::OfficialRole, seeded_random_source, team_side::TeamSide};
+
+    const CSV_CREW: &str = "name,role,head,team\n\
+        Head Ref, IPR, true,\n\
+        Ref Two, JR, false, home\n\
+        Ref Three, JR, false, away\n\
+        Ref Four, OPR, false,\n\
+        Ref Five, OPR, false,\n\
+        Head NSO, JT, true,\n\
+        NSO Two, SK, false,\n\
+        NSO Three, SK, false,\n\
+        NSO Four, SBO, false,\n\
+        NSO Five, PLT, false,\n\
+        NSO Six, PBT, false,\n";
+
+    fn crew() -> CrewFile {
+        CrewFile { officials: CrewFile::read_csv_officials(CSV_CREW).unwrap() }
+    }
+
+    fn official(name: &str, role: &str) -> CrewOfficial {
+        CrewOfficial { name: name.to_string(), role: role.to_string(), head: false, league: None, certification: None, team: None }
+    }
+
+    #[test]
+    fn csv_officials_are_read_with_optional_columns() {
+        let crew = crew();
+
+        assert_eq!(crew.officials.len(), 11);
+        assert!(crew.officials[0].head);
+        assert_eq!(crew.officials[0].team, None);
+        assert_eq!(crew.officials[1].team.as_deref(), Some("home"));
+    }
+
+    #[test]
+    fn valid_crew_passes_validation() {
+        assert_eq!(crew().validate(), Ok(()));
+    }
+
+    #[test]
+    fn official_without_a_name_is_rejected() {
+        let mut crew = crew();
+        crew.officials[0].name = String::new();
+
+        assert!(crew.validate().is_err());
+    }
+
+    #[test]
+    fn unknown_role_is_rejected() {
+        let mut crew = crew();
+        crew.officials.push(official("NSO Seven", "Mascot"));
+
+        assert!(crew.validate().is_err());
+    }
+
+    #[test]
+    fn unknown_team_is_rejected() {
+        let mut crew = crew();
+        crew.officials[1].team = Some("visitors".to_string());
+
+        assert!(crew.validate().is_err());
+    }
+
+    #[test]
+    fn team_for_a_role_without_teams_is_rejected() {
+        let mut crew = crew();
+        crew.officials[3].team = Some("home".to_string());
+
+        assert!(crew.validate().is_err());
+    }
+
+    #[test]
+    fn crew_missing_a_required_role_is_rejected() {
+        let mut crew = crew();
+        crew.officials.retain(|o| o.role != "SBO");
+
+        assert!(crew.validate().is_err());
+    }
+
+    #[test]
+    fn crew_with_too_few_referees_is_rejected() {
+        let mut crew = crew();
+        crew.officials.retain(|o| o.name != "Ref Five");
+
+        assert!(crew.validate().is_err());
+    }
+
+    #[test]
+    fn crew_without_a_head_referee_is_rejected() {
+        let mut crew = crew();
+        crew.officials[0].head = false;
+
+        assert!(crew.validate().is_err());
+    }
+
+    #[test]
+    fn crew_with_two_head_nsos_is_rejected() {
+        let mut crew = crew();
+        crew.officials[6].head = true;
+
+        assert!(crew.validate().is_err());
+    }
+
+    #[test]
+    fn team_roles_without_a_team_are_split_between_teams() {
+        let mut crew = crew();
+        crew.officials.push(official("NSO Seven", "PLT"));
+
+        let officials = crew.into_crew(&mut seeded_random_source(1)).unwrap();
+        let tracker_teams: Vec<Option<TeamSide>> = officials.iter().filter(|o| o.role == OfficialRole::PenaltyLineupTracker).map(|o| o.team).collect();
+
+        assert_eq!(tracker_teams, vec![Some(TeamSide::Home), Some(TeamSide::Away)]);
+        assert!(officials.iter().filter(|o| !o.role.is_team_assigned()).all(|o| o.team.is_none()));
+    }
+}
@@ -1,17 +1,44 @@
-use std::{cmp::Ordering, borrow::BorrowMut};
+use std::cmp::Ordering;
 
 use rand::{Rng, rngs::StdRng};
+use uuid::Uuid;
 
-use crate::{team::Team, official::Official, skater::{Skater, Position}, game_json::{GameJson, TeamJamFielding, FieldingSkaterJson, TeamJamJson}};
+use crate::{team::Team, official::Official, skater::{Skater, Position}, game_json::{GameJson, TeamJamFielding, FieldingSkaterJson, TeamJamJson}, game_events::{GameEvents, GameEventType, JamEndReason, TimeoutType}, team_side::TeamSide, penalty_code::{self, PenaltyCodeWeights}, pack::{self, Pack}, recording_errors::RecordedGame, rules::{FOUL_OUT_PENALTY_COUNT, INJURY_SIT_OUT_JAMS, INTERVAL_DURATION, JAM_DURATION, LINEUP_DURATION, PENALTY_SIT_DURATION, PERIOD_COUNT, PERIOD_DURATION, TEAM_TIMEOUT_COUNT, TEAM_TIMEOUT_DURATION}, team_pair::TeamPair};
 
-const PENALTY_SIT_DURATION: u64 = 30 * 1000;
-const PERIOD_DURATION: u64 = 30 * 60 * 1000;
-const JAM_DURATION: u64 = 2 * 60 * 1000;
-const LINEUP_DURATION: u64 = 30 * 1000;
+const TEAM_TIMEOUT_CHANCE: f64 = 1.0 / 1500.0;
+const TEAM_TIMEOUT_TRAILING_SCORE_SCALE: f64 = 20.0;
+const OFFICIAL_REVIEW_CHANCE: f64 = 1.0 / 2000.0;
+const OFFICIAL_REVIEW_RETAINED_CHANCE: f64 = 1.0 / 2.0;
+const OFFICIAL_TIMEOUT_CHANCE: f64 = 1.0 / 1200.0;
+
+/// Chance of a misconduct penalty being gross misconduct, which expels the skater.
+const GROSS_MISCONDUCT_CHANCE: f64 = 1.0 / 15.0;
+
+const JAM_INJURY_CHANCE: f64 = 1.0 / 4000.0;
 
 const RETURN_CUT_PENALTY_CHANCE: f64 = 1.0 / 100.0;
 const EXIT_PACK_NO_PASS_CHANCE: f64 = 1.0 / 50.0;
-const EXIT_PACK_CALL_CHANCE: f64 = 1.0 / 2.0;
+const EXIT_PACK_CALL_CHANCE: f64 = 1.0 / 5.0;
+
+const BLOCKER_COUNT: usize = 4;
+const OPPONENT_NOT_PASSED_CHANCE: f64 = 1.0 / 10.0;
+
+const PACK_SPEED: f32 = 4.0;
+const PACK_COHESION: f32 = 0.25;
+const BLOCKER_DRIFT: f32 = 1.5;
+const JAMMER_BLOCKED_SCALE: f32 = 1.5;
+const BLOCKER_CHASE_CHANCE: f64 = 1.0 / 300.0;
+const PACK_SPLIT_CHANCE: f64 = 1.0 / 400.0;
+const NO_PACK_COHESION_SCALE: f32 = 0.15;
+const OUT_OF_PLAY_COHESION_SCALE: f32 = 0.3;
+const FAILURE_TO_REFORM_DURATION: u64 = 3 * 1000;
+const FAILURE_TO_RETURN_DURATION: u64 = 3 * 1000;
+const ENGAGED_PENALTY_SCALE: f64 = 2.0;
+const DISENGAGED_PENALTY_SCALE: f64 = 0.5;
+
+const STAR_PASS_CHANCE: f64 = 1.0 / 5000.0;
+const STAR_PASS_IN_PACK_SCALE: f64 = 4.0;
+const STAR_PASS_OPPONENT_LEAD_SCALE: f64 = 3.0;
 
 type OnTrackTeam = Vec<JamSkater>;
 
@@ -19,19 +46,11 @@ type OnTrackTeam = Vec<JamSkater>;
 struct PreGame {
 }
 
-#[derive(Clone, Debug)]
-enum LeadJammerTeam {
-    None,
-    Home,
-    Away,
-}
-
 #[derive(Clone, Debug)]
 struct JamInProgress {
     start_tick: u64,
-    home_skaters: OnTrackTeam,
-    away_skaters: OnTrackTeam,
-    lead_jammer_team: LeadJammerTeam,
+    skaters: TeamPair<OnTrackTeam>,
+    lead_jammer_team: Option<TeamSide>,
 }
 
 #[derive(Clone, Debug)]
@@ -39,17 +58,12 @@ struct LineupInProgress {
     start_tick: u64,
 }
 
-#[derive(Clone, Debug)]
-enum TimeoutType {
-    Official,
-    Team,
-    Review,
-}
-
 #[derive(Clone, Debug)]
 struct TimeoutInProgress {
     start_tick: u64,
+    duration: u64,
     timeout_type: TimeoutType,
+    team: Option<TeamSide>,
 }
 
 #[derive(Clone, Debug)]
@@ -57,11 +71,6 @@ struct IntervalInProgress {
     start_tick: u64,
 }
 
-#[derive(Clone, Debug)]
-struct PostGame {
-    start_tick: u64,
-}
-
 #[derive(Clone, Debug)]
 enum GameState {
     PreGame(PreGame),
@@ -69,31 +78,47 @@ enum GameState {
     LineupInProgress(LineupInProgress),
     TimeoutInProgress(TimeoutInProgress),
     IntervalInProgress(IntervalInProgress),
-    PostGame(PostGame),
+    PostGame,
 }
 
 type PenaltyBox = Vec<JamSkater>;
 
+/// A team's state over the course of a game.
 #[derive(Clone)]
 pub struct GameTeam {
     pub details: Team,
-    timeouts_remaining: u8,
-    has_official_review: bool,
-    official_review_retained: bool,
-    roster: Vec<GameSkater>,
+    pub timeouts_remaining: usize,
+    pub has_official_review: bool,
+    pub official_review_retained: bool,
+    pub roster: Vec<GameSkater>,
 }
 
+/// A rostered skater and the penalties they have received.
 #[derive(Clone, Debug)]
-struct GameSkater {
-    details: Skater,
-    penalties: Vec<Penalty>,
-    last_jam_tick: u64,
+pub struct GameSkater {
+    pub details: Skater,
+    pub penalties: Vec<Penalty>,
+    pub last_jam_tick: u64,
+    pub is_expelled: bool,
+    pub injury_jams_remaining: usize,
+}
+
+impl GameSkater {
+    pub fn is_fouled_out(&self) -> bool {
+        self.penalties.len() >= FOUL_OUT_PENALTY_COUNT
+    }
+
+    /// Whether the skater may be fielded, i.e. has not fouled out, been expelled or been injured
+    /// in one of the last few jams.
+    pub fn is_eligible(&self) -> bool {
+        !self.is_fouled_out() && !self.is_expelled && self.injury_jams_remaining == 0
+    }
 }
 
 #[derive(Clone, Debug)]
-struct Penalty {
-    code: String,
-    received_tick: u64,
+pub struct Penalty {
+    pub code: String,
+    pub received_tick: u64,
 }
 
 #[derive(Clone, Debug)]
@@ -131,6 +156,8 @@ enum SkaterActivity {
     SatInBox(SatInBox),
     ReturningFromBox(ReturningFromBox),
     HeldInBox(HeldInBox),
+    /// Fouled out or expelled and released from the box, so no longer on track.
+    Removed,
 }
 
 #[derive(Clone, Debug)]
@@ -140,20 +167,22 @@ struct JamSkater {
     activity: SkaterActivity,
     can_receive_lead: bool,
     is_lead: bool,
+    is_passing_star: bool,
+    out_of_play_since: Option<u64>,
+    needs_substitute: bool,
 }
 
 impl From<Vec<JamSkater>> for TeamJamFielding {
     fn from(value: Vec<JamSkater>) -> Self {
-        let blockers: Vec<&JamSkater> = value.iter().filter(|s| s.position == Position::Blocker).collect();
-        let jammer = value.iter().filter(|s| s.position == Position::Jammer).next().unwrap();
-        let pivot = value.iter().filter(|s| s.position == Position::Pivot).next();
+        let fielded = |position: Position| value.iter().filter(move |s| s.position == position).map(|s| FieldingSkaterJson::from(s.clone()));
+        let mut blockers = fielded(Position::Blocker);
 
         TeamJamFielding {
-            blocker1: blockers[0].clone().into(),
-            blocker2: blockers[1].clone().into(),
-            blocker3: blockers[2].clone().into(),
-            jammer: jammer.clone().into(),
-            pivot: if let Some(p) = pivot { p.clone().into() } else { blockers[3].clone().into() },
+            blocker1: blockers.next(),
+            blocker2: blockers.next(),
+            blocker3: blockers.next(),
+            jammer: fielded(Position::Jammer).next(),
+            pivot: fielded(Position::Pivot).next().or_else(|| blockers.next()),
         }
     }
 }
@@ -162,49 +191,67 @@ impl From<JamSkater> for FieldingSkaterJson {
     fn from(value: JamSkater) -> Self {
         FieldingSkaterJson {
             skater_id: value.details.id,
-            number: "".to_string(),
+            number: value.details.number,
         }
     }
 }
 
-struct JamStartEvent {
-    tick: u64,
-}
-
+/// A simulated game, advanced one tick at a time until full time.
 pub struct Game {
     random_source: StdRng,
-    pub home_team: GameTeam,
-    pub away_team: GameTeam,
+    pub teams: TeamPair<GameTeam>,
     pub officials: Vec<Official>,
     pub game_json: GameJson,
+    pub game_events: GameEvents,
+    pub penalty_code_weights: PenaltyCodeWeights,
+    /// Whether to print the progress of the simulation to stdout.
+    pub verbose: bool,
     state: GameState,
     current_tick: u64,
     period_clock: u64,
     penalty_box: PenaltyBox,
     lead_is_open: bool,
     jam_called: bool,
+    no_pack_since: Option<u64>,
+    is_overtime: bool,
 }
 
 impl Game {
+    /// Creates a game between two random teams with a random officiating crew.
     pub fn random(mut random_source: StdRng) -> Game {
-        let home_team = Self::get_random_team(&mut random_source);
-        let away_team = Self::get_random_team(&mut random_source);
+        let home_team = Team::random(&mut random_source);
+        let away_team = Team::random(&mut random_source);
         let officials = Official::random_crew(&mut random_source);
 
+        Self::new(random_source, TeamPair::new(home_team, away_team), officials)
+    }
+
+    /// Creates a game between the given teams and officiating crew.
+    pub fn new(mut random_source: StdRng, teams: TeamPair<Team>, officials: Vec<Official>) -> Game {
+        let game_json = GameJson::new(&mut random_source);
+
         let mut game = Game {
             random_source,
-            home_team,
-            away_team,
+            teams: teams.map(|_, t| Self::get_game_team(t)),
             officials,
-            game_json: GameJson::new(),
+            game_json,
+            game_events: GameEvents::new(),
+            penalty_code_weights: PenaltyCodeWeights::default(),
+            verbose: false,
             state: GameState::PreGame(PreGame {}),
             current_tick: 0,
             period_clock: 0,
             penalty_box: PenaltyBox::default(),
             lead_is_open: false,
             jam_called: false,
+            no_pack_since: None,
+            is_overtime: false,
         };
 
+        for (team, game_team) in game.teams.iter() {
+            game.game_json.add_team(team, &game_team.details);
+        }
+
         for official in game.officials.iter() {
             game.game_json.add_official(official);
         }
@@ -212,9 +259,10 @@ impl Game {
         game
     }
 
+    /// Simulates the game until it has ended.
     pub fn run(&mut self) {
         loop {
-            if let GameState::PostGame(_) = self.state {
+            if let GameState::PostGame = self.state {
                 break;
             }
 
@@ -222,18 +270,23 @@ impl Game {
         }
     }
 
-    fn get_random_team(random_source: &mut StdRng) -> GameTeam {
-        let team = Team::random(random_source);
+    /// Copies the game as recorded by officials who make mistakes, see [`RecordedGame`] for the errors injected.
+    pub fn record_with_errors(&mut self, error_rate: f64) -> RecordedGame {
+        RecordedGame::new(&self.game_json, error_rate, &mut self.random_source)
+    }
 
+    fn get_game_team(team: &Team) -> GameTeam {
         GameTeam {
             details: team.clone(),
-            timeouts_remaining: 3,
+            timeouts_remaining: TEAM_TIMEOUT_COUNT,
             has_official_review: true,
             official_review_retained: false,
             roster: team.clone().roster.iter().map(|s| GameSkater {
                 details: s.clone(),
                 penalties: vec![],
                 last_jam_tick: 0,
+                is_expelled: false,
+                injury_jams_remaining: 0,
             }).collect(),
         }
     }
@@ -244,6 +297,8 @@ impl Game {
             GameState::PreGame(_) => self.start_jam(),
             GameState::JamInProgress(jam) => self.tick_jam(&jam),
             GameState::LineupInProgress(lineup) => self.tick_lineup(&lineup),
+            GameState::TimeoutInProgress(timeout) => self.tick_timeout(&timeout),
+            GameState::IntervalInProgress(interval) => self.tick_interval(&interval),
             _ => self.end_game()
         };
     }
@@ -251,57 +306,126 @@ impl Game {
     fn start_jam(&mut self) -> GameState {
         let jam_start_tick = self.get_random_current_tick();
 
-        if self.period_clock == 0 {
+        if self.period_clock == 0 && !self.is_overtime {
             self.game_json.add_period(jam_start_tick);
-            self.period_clock = 30 * 60 * 1000 + jam_start_tick;
+            self.period_clock = PERIOD_DURATION;
+
+            for (_, team) in self.teams.iter_mut() {
+                team.has_official_review = true;
+                team.official_review_retained = false;
+            }
+
+            self.log_progress(&format!("Period {} started", self.game_json.period_count()));
+            self.game_events.add(jam_start_tick, GameEventType::PeriodStarted { period: self.game_json.period_count() });
         }
 
-        let home_skaters = self.get_random_jam_team(&self.home_team.clone());
-        let away_skaters = self.get_random_jam_team(&self.away_team.clone());
+        let mut released_skater_ids = Vec::new();
+        let skaters = TeamPair::new(
+            self.get_random_jam_team(TeamSide::Home, &mut released_skater_ids),
+            self.get_random_jam_team(TeamSide::Away, &mut released_skater_ids),
+        );
+
+        for (_, team) in self.teams.iter_mut() {
+            for skater in team.roster.iter_mut() {
+                skater.injury_jams_remaining = skater.injury_jams_remaining.saturating_sub(1);
+            }
+        }
 
         let jam = JamInProgress { 
             start_tick: jam_start_tick,
-            home_skaters,
-            away_skaters,
-            lead_jammer_team: LeadJammerTeam::None,
+            skaters,
+            lead_jammer_team: None,
         };
 
-        self.game_json.current_period_mut().unwrap().add_jam(jam_start_tick, &jam.home_skaters.clone().into(), &jam.away_skaters.clone().into());
-        self.game_json.current_period_mut().unwrap().current_jam_mut().unwrap().home_team_jam.add_trip(jam_start_tick);
-        self.game_json.current_period_mut().unwrap().current_jam_mut().unwrap().away_team_jam.add_trip(jam_start_tick);
+        let period_clock_elapsed = self.get_period_clock_elapsed();
+        let jam_id = self.game_json.new_id();
+        self.game_json.current_period_mut().unwrap().add_jam(jam_id, jam_start_tick, period_clock_elapsed, &jam.skaters.map(|_, s| s.clone().into()));
+
+        for skater_id in released_skater_ids {
+            self.game_json.end_box_trip(skater_id, self.current_tick, true);
+        }
+
+        for team in [TeamSide::Home, TeamSide::Away] {
+            let trip_id = self.game_json.new_id();
+            self.get_current_team_jam_mut(team).add_trip(trip_id, jam_start_tick);
+        }
+
+        self.game_json.current_period_mut().unwrap().current_jam_mut().unwrap().is_overtime = self.is_overtime;
+
+        let jam_number = self.game_json.current_period_mut().unwrap().jam_count();
+        self.log_progress(&format!("Jam {} started", jam_number));
 
-        println!("Jam {} started", self.game_json.current_period_mut().unwrap().jam_count());
+        self.game_events.add(jam_start_tick, GameEventType::JamStarted { period: self.game_json.period_count(), jam: jam_number });
 
-        self.lead_is_open = true;
+        self.lead_is_open = !self.is_overtime;
         self.jam_called = false;
+        self.no_pack_since = None;
 
         GameState::JamInProgress(jam)
     }
 
     fn end_game(&self) -> GameState {
-        GameState::PostGame(PostGame { start_tick: self.current_tick })
+        GameState::PostGame
+    }
+
+    fn end_period(&mut self, period_end_tick: u64) -> GameState {
+        let period_number = self.game_json.period_count();
+        let period = self.game_json.current_period_mut().unwrap();
+        period.duration = period_end_tick - period.start_tick;
+
+        self.log_progress(&format!("Period {} ended", period_number));
+        self.game_events.add(period_end_tick, GameEventType::PeriodEnded { period: period_number });
+
+        if period_number < PERIOD_COUNT {
+            GameState::IntervalInProgress(IntervalInProgress { start_tick: period_end_tick })
+        } else {
+            self.game_events.add(period_end_tick, GameEventType::GameEnded);
+
+            GameState::PostGame
+        }
     }
 
-    fn end_jam(&mut self, jam: &JamInProgress, jam_end_tick: u64, was_called: bool) -> GameState {
+    fn end_jam(&mut self, jam: &JamInProgress, jam_end_tick: u64, reason: JamEndReason) -> GameState {
+        let period_clock_elapsed = self.get_period_clock_elapsed();
         let jam_json = self.game_json.current_period_mut().unwrap().current_jam_mut().unwrap();
         jam_json.end_tick = jam_end_tick;
+        jam_json.period_clock_elapsed_end = period_clock_elapsed;
 
-        let mut end_team_jam = |team_jam: &mut TeamJamJson| {
-            let trip_count = team_jam.trip_count();
-            let trip = team_jam.current_trip_mut().unwrap();
-            println!("{} - {}", jam_end_tick, trip.start_tick);
-            trip.duration = if trip.start_tick < jam_end_tick { jam_end_tick - trip.start_tick } else { 0 };
-            trip.score = if trip_count > 1 { self.random_source.gen_range(0..=4) } else { 0 };
-        };
+        for team in [TeamSide::Home, TeamSide::Away] {
+            self.end_team_jam(&jam.skaters, team, jam_end_tick);
+
+            let team_jam = self.get_current_team_jam_mut(team);
+            match reason {
+                JamEndReason::Called => team_jam.called_off = jam.lead_jammer_team == Some(team),
+                JamEndReason::Injury => team_jam.injury = true,
+                JamEndReason::Expired => {},
+            }
+        }
+
+        let period_number = self.game_json.period_count();
 
-        end_team_jam(&mut jam_json.home_team_jam);
+        let jam_number = self.game_json.current_period_mut().unwrap().jam_count();
+        self.game_events.add(jam_end_tick, GameEventType::JamEnded { period: period_number, jam: jam_number, reason });
 
         let period_has_expired = self.period_clock == 0;
 
         for skater in self.penalty_box.iter_mut() {
-            let track_skater = jam.home_skaters.iter().chain(jam.away_skaters.iter()).filter(|s| s.details.id == skater.details.id).next().unwrap();
+            let (team, track_skater) = jam.skaters.iter()
+                .flat_map(|(team, skaters)| skaters.iter().map(move |s| (team, s)))
+                .find(|(_, s)| s.details.id == skater.details.id)
+                .unwrap();
+            skater.needs_substitute = track_skater.needs_substitute;
             skater.activity = match &track_skater.activity {
                 SkaterActivity::SkatingToBox(skating_to_box) => {
+
+                    self.game_json.start_box_trip(team, skater.details.id, jam_end_tick, true);
+                    self.game_events.add(jam_end_tick, GameEventType::BoxEntered {
+                        team,
+                        skater_id: skater.details.id,
+                        skater_name: skater.details.name.clone(),
+                        penalty_count: skating_to_box.penalties_to_sit,
+                    });
+
                     SkaterActivity::HeldInBox(HeldInBox { 
                         ticks_expired: 0,
                         penalty_count: skating_to_box.penalties_to_sit,
@@ -309,7 +433,7 @@ impl Game {
                 },
                 SkaterActivity::SatInBox(sat_in_box) => {
                     SkaterActivity::HeldInBox(HeldInBox { 
-                        ticks_expired: jam_end_tick - sat_in_box.start_tick,
+                        ticks_expired: jam_end_tick.saturating_sub(sat_in_box.start_tick),
                         penalty_count: sat_in_box.penalty_count,
                     })
                 },
@@ -319,57 +443,252 @@ impl Game {
             }
         }
 
-        if period_has_expired {
-            let period = self.game_json.current_period_mut().unwrap();
-            period.duration = jam_end_tick - period.start_tick;
-
-            GameState::IntervalInProgress(IntervalInProgress { start_tick: jam_end_tick })
+        if period_has_expired && self.is_tied_after_regulation() {
+            self.start_overtime(jam_end_tick)
+        } else if period_has_expired {
+            self.end_period(jam_end_tick)
         } else {
             GameState::LineupInProgress(LineupInProgress { start_tick: jam_end_tick })
         }
     }
 
+    fn is_tied_after_regulation(&self) -> bool {
+        self.game_json.period_count() >= PERIOD_COUNT && self.game_json.get_team_score(TeamSide::Home) == self.game_json.get_team_score(TeamSide::Away)
+    }
+
+    fn start_overtime(&mut self, start_tick: u64) -> GameState {
+        if !self.is_overtime {
+            self.is_overtime = true;
+
+            self.log_progress("Overtime");
+            self.game_events.add(start_tick, GameEventType::OvertimeStarted { period: self.game_json.period_count() });
+        }
+
+        GameState::LineupInProgress(LineupInProgress { start_tick })
+    }
+
+    fn end_team_jam(&mut self, jam_skaters: &TeamPair<OnTrackTeam>, team: TeamSide, jam_end_tick: u64) {
+        let trip_number = self.get_current_team_jam_mut(team).trip_count();
+        if self.get_current_team_jam_mut(team).current_trip_mut().unwrap().is_complete {
+            return;
+        }
+
+        let pack = Self::find_pack(jam_skaters);
+        let opponents = &jam_skaters[team.opponent()];
+        let jammer_location = jam_skaters[team].iter()
+            .filter(|s| s.position == Position::Jammer)
+            .find_map(|s| match s.activity {
+                SkaterActivity::SkatingOnTrack(SkatingOnTrack { location }) if pack.contains(location) => Some(location),
+                _ => None,
+            });
+        let score = match jammer_location {
+            Some(jammer_location) => self.get_random_trip_score(opponents, &pack, jammer_location, team, trip_number, false),
+            None => 0,
+        };
+
+        let trip = self.get_current_team_jam_mut(team).current_trip_mut().unwrap();
+        trip.duration = jam_end_tick.saturating_sub(trip.start_tick);
+        trip.score = score;
+
+        self.game_events.add(jam_end_tick, GameEventType::TripCompleted { team, trip: trip_number, points: score });
+    }
+
     fn tick_jam(&mut self, jam: &JamInProgress) -> GameState {
-        let jam_has_expired = self.current_tick - jam.start_tick >= 120000;
+        let jam_has_expired = self.current_tick - jam.start_tick >= JAM_DURATION;
 
-        self.period_clock = if self.period_clock >= 1000 { self.period_clock - 1000 } else { 0 };
+        self.period_clock = self.period_clock.saturating_sub(1000);
 
         if jam_has_expired {
-            let jam_end_tick = jam.start_tick + 120000;
+            let jam_end_tick = jam.start_tick + JAM_DURATION;
 
-            println!("Jam expired");
-            self.end_jam(jam, jam_end_tick, false)
+            self.log_progress("Jam expired");
+            self.end_jam(jam, jam_end_tick, JamEndReason::Expired)
         } else {
-            let mut home_skaters = jam.home_skaters.clone();
-            for skater in home_skaters.iter_mut() {
-                self.tick_skater(skater, true);
+            let blockers = Self::get_blocker_locations(&jam.skaters);
+            let detected_pack = Pack::find(&blockers);
+            let pack = detected_pack.clone().unwrap_or_else(|| Pack::spanning(&blockers));
+
+            let mut skaters = jam.skaters.clone();
+            let first_team = if self.random_source.gen_bool(0.5) { TeamSide::Home } else { TeamSide::Away };
+            for team in [first_team, first_team.opponent()] {
+                let opponents = &jam.skaters[team.opponent()];
+                for skater in skaters[team].iter_mut() {
+                    self.tick_skater(skater, opponents, &pack, team);
+                }
+                self.complete_star_pass(&mut skaters[team], team);
             }
 
-            let mut away_skaters = jam.away_skaters.clone();
-            for skater in away_skaters.iter_mut() {
-                self.tick_skater(skater, false);
+            self.tick_pack(detected_pack.is_some(), &pack, &mut skaters);
+
+            for team in [TeamSide::Home, TeamSide::Away] {
+                self.substitute_removed_skaters(&mut skaters[team], team);
             }
 
+            let lead_jammer_team = jam.lead_jammer_team
+                .or_else(|| [TeamSide::Home, TeamSide::Away].into_iter().find(|t| self.get_current_team_jam_mut(*t).is_lead));
+
+            let updated_jam = JamInProgress {
+                start_tick: jam.start_tick,
+                skaters,
+                lead_jammer_team,
+            };
+
             if self.jam_called {
                 let jam_end_tick = self.get_random_current_tick();
 
-                println!("Jam called");
-                self.end_jam(jam, jam_end_tick, true)
+                self.log_progress("Jam called");
+                self.end_jam(&updated_jam, jam_end_tick, JamEndReason::Called)
+            } else if self.random_source.gen_bool(JAM_INJURY_CHANCE) && self.injure_random_skater(&updated_jam) {
+                let jam_end_tick = self.get_random_current_tick();
+
+                self.log_progress("Jam called for injury");
+                self.end_jam(&updated_jam, jam_end_tick, JamEndReason::Injury)
             } else {
-                GameState::JamInProgress(JamInProgress {
-                    start_tick: jam.start_tick,
-                    home_skaters: home_skaters,
-                    away_skaters: away_skaters.clone(),
-                    lead_jammer_team: jam.lead_jammer_team.clone(),
-                })
+                GameState::JamInProgress(updated_jam)
             }
         }
     }
 
-    fn give_skater_penalty(&mut self, skater: &mut JamSkater) -> SkaterActivity {
-        println!("Penalty for {}", skater.details.name);
+    fn injure_random_skater(&mut self, jam: &JamInProgress) -> bool {
+        let on_track_skaters: Vec<(TeamSide, &JamSkater)> = jam.skaters.iter()
+            .flat_map(|(team, skaters)| skaters.iter().map(move |s| (team, s)))
+            .filter(|(_, s)| matches!(s.activity, SkaterActivity::SkatingOnTrack(_)))
+            .collect();
+
+        if on_track_skaters.is_empty() {
+            return false;
+        }
+
+        let (team, skater) = on_track_skaters[self.random_source.gen_range(0..on_track_skaters.len())];
+        let (skater_id, skater_name) = (skater.details.id, skater.details.name.clone());
+
+        if let Some(game_skater) = self.teams[team].roster.iter_mut().find(|s| s.details.id == skater_id) {
+            game_skater.injury_jams_remaining = INJURY_SIT_OUT_JAMS;
+        }
+        self.get_current_team_jam_mut(team).injured_skater_id = Some(skater_id);
+
+        self.log_progress(&format!("{} injured", skater_name));
+        self.game_events.add(self.current_tick, GameEventType::SkaterInjured { team, skater_id, skater_name });
+
+        true
+    }
+
+    fn record_skater_penalty(&mut self, skater: &mut JamSkater, team: TeamSide, code: String) {
+        self.log_progress(&format!("Penalty for {} ({})", skater.details.name, code));
+        self.game_events.add(self.current_tick, GameEventType::PenaltyIssued {
+            team,
+            skater_id: skater.details.id,
+            skater_name: skater.details.name.clone(),
+            code: code.clone(),
+        });
+
+        self.game_json.add_penalty(team, skater.details.id, &code, self.current_tick);
+
+        let is_expelled = code == penalty_code::MISCONDUCT && self.random_source.gen_bool(GROSS_MISCONDUCT_CHANCE);
+
+        let game_skater = self.teams[team].roster.iter_mut().find(|s| s.details.id == skater.details.id).unwrap();
+        let was_eligible = game_skater.is_eligible();
+        game_skater.penalties.push(Penalty {
+            code,
+            received_tick: self.current_tick,
+        });
+        game_skater.is_expelled |= is_expelled;
+
+        if was_eligible && !game_skater.is_eligible() {
+            let (skater_id, skater_name) = (skater.details.id, skater.details.name.clone());
+
+            if is_expelled {
+                self.log_progress(&format!("{} expelled", skater_name));
+                self.game_events.add(self.current_tick, GameEventType::Expelled { team, skater_id, skater_name });
+                self.game_json.add_removal(team, skater_id, penalty_code::EXPELLED, self.current_tick);
+            } else {
+                self.log_progress(&format!("{} fouled out", skater_name));
+                self.game_events.add(self.current_tick, GameEventType::FouledOut { team, skater_id, skater_name });
+                self.game_json.add_removal(team, skater_id, penalty_code::FOULED_OUT, self.current_tick);
+            }
+
+            skater.needs_substitute = true;
+        }
+    }
+
+    fn substitute_removed_skaters(&mut self, skaters: &mut OnTrackTeam, team: TeamSide) {
+        for index in 0..skaters.len() {
+            if !skaters[index].needs_substitute {
+                continue;
+            }
+
+            let substitutes: Vec<&GameSkater> = self.teams[team].roster.iter()
+                .filter(|s| s.is_eligible())
+                .filter(|s| !skaters.iter().chain(self.penalty_box.iter()).any(|o| o.details.id == s.details.id))
+                .collect();
+
+            if substitutes.is_empty() {
+                continue;
+            }
+
+            skaters[index].needs_substitute = false;
+            for boxed_skater in self.penalty_box.iter_mut().filter(|s| s.details.id == skaters[index].details.id) {
+                boxed_skater.needs_substitute = false;
+            }
+
+            let substitute = substitutes[self.random_source.gen_range(0..substitutes.len())].details.clone();
+            let removed = std::mem::replace(&mut skaters[index].details, substitute.clone());
+
+            for boxed_skater in self.penalty_box.iter_mut().filter(|s| s.details.id == removed.id) {
+                boxed_skater.details = substitute.clone();
+            }
+            self.game_json.substitute_penalty_server(removed.id, substitute.id);
+
+            self.log_progress(&format!("{} serving for {}", substitute.name, removed.name));
+            self.game_events.add(self.current_tick, GameEventType::BoxSubstitution {
+                team,
+                skater_id: removed.id,
+                skater_name: removed.name,
+                substitute_id: substitute.id,
+                substitute_name: substitute.name,
+            });
+        }
+    }
+
+    /// Removes skaters who fouled out or were expelled from the box when no teammate is left to serve for them,
+    /// leaving their position empty. Their box trips are ended between jams once the next jam has been added.
+    fn release_unsubstituted_skaters(&mut self, skaters: &mut OnTrackTeam, team: TeamSide, released_skater_ids: &mut Vec<Uuid>) {
+        let (removed, remaining): (Vec<JamSkater>, Vec<JamSkater>) = skaters.drain(..).partition(|s| s.needs_substitute);
+        *skaters = remaining;
+
+        for skater in removed {
+            self.penalty_box.retain(|s| s.details.id != skater.details.id);
+            released_skater_ids.push(skater.details.id);
+
+            self.log_progress(&format!("Releasing {} with no substitute", skater.details.name));
+            self.game_events.add(self.current_tick, GameEventType::BoxReleased {
+                team,
+                skater_id: skater.details.id,
+                skater_name: skater.details.name,
+            });
+        }
+    }
+
+    fn give_skater_penalty(&mut self, skater: &mut JamSkater, team: TeamSide) -> SkaterActivity {
+        let code = self.penalty_code_weights.random_code(&mut self.random_source);
+        self.give_skater_penalty_with_code(skater, team, code)
+    }
+
+    fn give_skater_penalty_with_code(&mut self, skater: &mut JamSkater, team: TeamSide, code: String) -> SkaterActivity {
+        self.record_skater_penalty(skater, team, code);
+
+        if skater.is_lead {
+            self.get_current_team_jam_mut(team).lost_lead = true;
+            self.game_events.add(self.current_tick, GameEventType::LeadLost {
+                team,
+                skater_id: skater.details.id,
+                skater_name: skater.details.name.clone(),
+            });
+        }
+
         skater.is_lead = false;
         skater.can_receive_lead = false;
+        skater.out_of_play_since = None;
         self.penalty_box.push(skater.clone());
 
         SkaterActivity::SkatingToBox(SkatingToBox { 
@@ -378,56 +697,56 @@ impl Game {
         })
     }
 
-    fn tick_on_track_skater(&mut self, on_track: &SkatingOnTrack, skater: &mut JamSkater, is_home_team: bool) -> SkaterActivity {
+    fn tick_on_track_skater(&mut self, on_track: &SkatingOnTrack, skater: &mut JamSkater, opponents: &OnTrackTeam, pack: &Pack, team: TeamSide) -> SkaterActivity {
         let has_commited_penalty = self.random_source.gen_bool(skater.details.penalty_chance);
 
-        let set_is_lead = |game_json: &mut GameJson, is_lead: bool| {
-            let team_json = if is_home_team { 
-                game_json.current_period_mut().unwrap().current_jam_mut().unwrap().home_team_jam.borrow_mut()
-            } else {
-                game_json.current_period_mut().unwrap().current_jam_mut().unwrap().away_team_jam.borrow_mut()
-            };
-            team_json.is_lead = is_lead;
-        };
-
         if has_commited_penalty {
-            set_is_lead(&mut self.game_json, false);
-            self.give_skater_penalty(skater)
+            self.give_skater_penalty(skater, team)
         } else {
             if skater.position == Position::Jammer {
-                let is_in_pack = on_track.location < 20.0;
+                let relative_location = pack.relative_location(on_track.location);
+                let is_in_pack = pack.contains(on_track.location) || pack.is_at_rear(on_track.location);
+
+                skater.is_passing_star = self.get_random_star_pass(skater, is_in_pack, team);
 
                 if is_in_pack {
-                    let new_location = on_track.location + self.random_source.gen_range(-2.0..skater.details.base_speed / 4.0);
+                    let blockers_in_position = Self::get_blockers_in_position(opponents, pack, on_track.location);
+                    let max_progress = skater.details.base_speed / (1.0 + JAMMER_BLOCKED_SCALE * blockers_in_position as f32);
+                    let new_location = on_track.location + PACK_SPEED + self.random_source.gen_range(-1.0..max_progress);
 
-                    let has_exited_pack = new_location >= 20.0;
+                    let has_exited_pack = !pack.contains(new_location) && !pack.is_approaching(new_location);
 
                     if has_exited_pack {
                         let pass_completion_tick = self.get_random_current_tick();
+                        let completed_trip_number = self.complete_trip(opponents, pack, new_location, team, pass_completion_tick);
+                        let has_completed_scoring_pass = completed_trip_number.is_some_and(|t| t > 1);
 
-                        let team = if is_home_team {
-                            self.game_json.current_period_mut().unwrap().current_jam_mut().unwrap().home_team_jam.borrow_mut()
-                        } else {
-                            self.game_json.current_period_mut().unwrap().current_jam_mut().unwrap().away_team_jam.borrow_mut()
-                        };
-
-                        if let Some(trip) = team.current_trip_mut() {
-                            trip.score = 4;
-                            trip.duration = pass_completion_tick - trip.start_tick;
-                        }
-
-                        if skater.is_lead {
+                        if skater.is_lead && has_completed_scoring_pass {
                             self.jam_called = self.random_source.gen_bool(EXIT_PACK_CALL_CHANCE);
+
+                            if self.jam_called {
+                                self.game_events.add(pass_completion_tick, GameEventType::JamCalledOff {
+                                    team,
+                                    skater_id: skater.details.id,
+                                    skater_name: skater.details.name.clone(),
+                                });
+                            }
                         }
 
                         let could_receive_lead = self.lead_is_open && skater.can_receive_lead;
                         if could_receive_lead {
                             let lead_earned = !self.random_source.gen_bool(EXIT_PACK_NO_PASS_CHANCE);
                             if lead_earned {
-                                set_is_lead(&mut self.game_json, true);
+                                self.get_current_team_jam_mut(team).is_lead = true;
 
                                 self.lead_is_open = false;
                                 skater.is_lead = true;
+
+                                self.game_events.add(pass_completion_tick, GameEventType::LeadAwarded {
+                                    team,
+                                    skater_id: skater.details.id,
+                                    skater_name: skater.details.name.clone(),
+                                });
                             }
                             skater.can_receive_lead = false;
                         }
@@ -435,72 +754,352 @@ impl Game {
 
                     let has_received_penalty = self.random_source.gen_bool(skater.details.penalty_chance);
                     if has_received_penalty {
-                        self.give_skater_penalty(skater)
+                        self.give_skater_penalty(skater, team)
                     } else {
                         SkaterActivity::SkatingOnTrack(SkatingOnTrack {
                             location: new_location,
                         })
                     }
                 } else {
-                    let mut new_location = on_track.location + skater.details.base_speed;
-                    if new_location > 100.0 {
-                        new_location = 0.0;
-
-                        let pass_start_tick = self.get_random_current_tick();
-                        let team = if is_home_team {
-                            self.game_json.current_period_mut().unwrap().current_jam_mut().unwrap().home_team_jam.borrow_mut()
-                        } else {
-                            self.game_json.current_period_mut().unwrap().current_jam_mut().unwrap().away_team_jam.borrow_mut()
-                        };
-
-                        team.add_trip(pass_start_tick);
+                    let mut new_location = on_track.location + skater.details.base_speed + self.random_source.gen_range(-1.0..1.0);
+
+                    let has_overtaken_rear = pack.is_approaching(on_track.location) && pack.relative_location(new_location) < relative_location;
+                    let has_entered_pack = has_overtaken_rear || pack.is_at_rear(new_location);
+                    if has_entered_pack {
+                        new_location = new_location.min(on_track.location + pack::TRACK_LENGTH - relative_location);
+                        self.start_trip_if_complete(team);
                     }
+
                     SkaterActivity::SkatingOnTrack(SkatingOnTrack {
                         location: new_location,
                     })
                 }
             } else {
-                let has_received_penalty = self.random_source.gen_bool(skater.details.penalty_chance);
+                let is_out_of_play = self.no_pack_since.is_none() && !pack.is_in_engagement_zone(on_track.location);
+                if !is_out_of_play {
+                    skater.out_of_play_since = None;
+                } else if let Some(out_of_play_since) = skater.out_of_play_since {
+                    if self.current_tick - out_of_play_since >= FAILURE_TO_RETURN_DURATION {
+                        self.game_events.add(self.current_tick, GameEventType::FailureToReturn {
+                            team,
+                            skater_id: skater.details.id,
+                            skater_name: skater.details.name.clone(),
+                        });
+                        return self.give_skater_penalty_with_code(skater, team, penalty_code::ILLEGAL_POSITION.to_string());
+                    }
+                } else {
+                    self.log_progress(&format!("{} out of play", skater.details.name));
+                    skater.out_of_play_since = Some(self.current_tick);
+                    self.game_events.add(self.current_tick, GameEventType::OutOfPlayWarning {
+                        team,
+                        skater_id: skater.details.id,
+                        skater_name: skater.details.name.clone(),
+                    });
+                }
+
+                let is_engaged = self.no_pack_since.is_none() && pack.contains(on_track.location) && opponents.iter()
+                    .filter(|s| s.position == Position::Jammer)
+                    .any(|s| matches!(s.activity, SkaterActivity::SkatingOnTrack(SkatingOnTrack { location }) if pack.is_in_engagement_zone(location)));
+                let penalty_scale = if is_engaged { ENGAGED_PENALTY_SCALE } else { DISENGAGED_PENALTY_SCALE };
+
+                let has_received_penalty = self.random_source.gen_bool(skater.details.penalty_chance * penalty_scale);
                 if has_received_penalty {
-                    self.give_skater_penalty(skater)
+                    self.give_skater_penalty(skater, team)
                 } else {
-                    skater.activity.clone()
+                    let cohesion_scale = if self.no_pack_since.is_some() {
+                        NO_PACK_COHESION_SCALE
+                    } else if is_out_of_play {
+                        self.random_source.gen_range(OUT_OF_PLAY_COHESION_SCALE..1.0)
+                    } else {
+                        1.0
+                    };
+                    let cohesion = (pack.center() - on_track.location) * PACK_COHESION * cohesion_scale;
+                    let mut drift = self.random_source.gen_range(-BLOCKER_DRIFT..BLOCKER_DRIFT);
+                    if is_engaged && self.random_source.gen_bool(BLOCKER_CHASE_CHANCE) {
+                        drift += self.random_source.gen_range(pack::ENGAGEMENT_ZONE_LENGTH..pack::ENGAGEMENT_ZONE_LENGTH * 2.0);
+                    }
+
+                    SkaterActivity::SkatingOnTrack(SkatingOnTrack {
+                        location: on_track.location + PACK_SPEED + cohesion + drift,
+                    })
                 }
             }
         }
     }
 
-    fn tick_skating_to_box_skater(&mut self, to_box: &SkatingToBox, skater: &mut JamSkater) -> SkaterActivity {
+    fn start_trip_if_complete(&mut self, team: TeamSide) {
+        let trip_start_tick = self.get_random_current_tick();
+        let trip_id = self.game_json.new_id();
+        let team_jam = self.get_current_team_jam_mut(team);
+
+        if team_jam.current_trip_mut().is_none_or(|t| t.is_complete) {
+            team_jam.add_trip(trip_id, trip_start_tick);
+        }
+    }
+
+    /// Scores and completes the jammer's current trip, returning its number, or `None` if it was already complete.
+    fn complete_trip(&mut self, opponents: &OnTrackTeam, pack: &Pack, jammer_location: f32, team: TeamSide, pass_completion_tick: u64) -> Option<usize> {
+        let trip_number = self.get_current_team_jam_mut(team).trip_count();
+        let score = self.get_random_trip_score(opponents, pack, jammer_location, team, trip_number, true);
+
+        let trip = self.get_current_team_jam_mut(team).current_trip_mut().unwrap();
+        if trip.is_complete {
+            return None;
+        }
+
+        trip.score = score;
+        trip.duration = pass_completion_tick.saturating_sub(trip.start_tick);
+        trip.is_complete = true;
+
+        self.game_events.add(pass_completion_tick, GameEventType::TripCompleted {
+            team,
+            trip: trip_number,
+            points: score,
+        });
+
+        Some(trip_number)
+    }
+
+    fn get_random_trip_score(&mut self, opponents: &OnTrackTeam, pack: &Pack, jammer_location: f32, team: TeamSide, trip_number: usize, has_completed_pass: bool) -> u8 {
+        if trip_number <= 1 || pack.is_approaching(jammer_location) {
+            return 0;
+        }
+
+        let jammer_relative_location = pack.relative_location(jammer_location);
+        let has_passed = |location: f32| pack.relative_location(location) < jammer_relative_location;
+
+        let opposing_blockers: Vec<&JamSkater> = opponents.iter().filter(|s| s.position != Position::Jammer).collect();
+
+        let mut passed_count = 0;
+        let mut not_on_track_count = BLOCKER_COUNT.saturating_sub(opposing_blockers.len()) as u8;
+        for blocker in opposing_blockers {
+            match blocker.activity {
+                SkaterActivity::SkatingOnTrack(SkatingOnTrack { location }) => {
+                    if has_passed(location) && !self.random_source.gen_bool(OPPONENT_NOT_PASSED_CHANCE) {
+                        passed_count += 1;
+                    }
+                },
+                _ => not_on_track_count += 1,
+            }
+        }
+
+        let has_earned_not_on_track_points = passed_count > 0 || has_completed_pass;
+        let mut score = passed_count + if has_earned_not_on_track_points { not_on_track_count } else { 0 };
+
+        let opposing_trip_number = self.get_current_team_jam_mut(team.opponent()).trip_count();
+        let has_lapped_opposing_jammer = opponents.iter()
+            .filter(|s| s.position == Position::Jammer)
+            .any(|s| matches!(s.activity, SkaterActivity::SkatingOnTrack(SkatingOnTrack { location }) if pack.contains(location) && has_passed(location)));
+        if has_lapped_opposing_jammer && opposing_trip_number < trip_number {
+            score += 1;
+        }
+
+        score
+    }
+
+    fn get_blockers_in_position(blockers: &OnTrackTeam, pack: &Pack, jammer_location: f32) -> usize {
+        let jammer_relative_location = if pack.is_at_rear(jammer_location) { 0.0 } else { pack.relative_location(jammer_location) };
+
+        blockers.iter()
+            .filter(|s| s.position != Position::Jammer)
+            .filter(|s| matches!(s.activity, SkaterActivity::SkatingOnTrack(SkatingOnTrack { location }) if pack.contains(location) && pack.relative_location(location) >= jammer_relative_location))
+            .count()
+    }
+
+    fn get_blocker_locations(jam_skaters: &TeamPair<OnTrackTeam>) -> Vec<(TeamSide, f32)> {
+        jam_skaters.iter()
+            .flat_map(|(team, skaters)| skaters.iter().map(move |s| (team, s)))
+            .filter(|(_, s)| s.position != Position::Jammer)
+            .filter_map(|(team, s)| match s.activity {
+                SkaterActivity::SkatingOnTrack(SkatingOnTrack { location }) => Some((team, location)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn find_pack(jam_skaters: &TeamPair<OnTrackTeam>) -> Pack {
+        let blockers = Self::get_blocker_locations(jam_skaters);
+
+        Pack::find(&blockers).unwrap_or_else(|| Pack::spanning(&blockers))
+    }
+
+    fn tick_pack(&mut self, has_pack: bool, pack: &Pack, jam_skaters: &mut TeamPair<OnTrackTeam>) {
+        match self.no_pack_since {
+            None if !has_pack => {
+                self.log_progress("No pack");
+                self.no_pack_since = Some(self.current_tick);
+                self.game_events.add(self.current_tick, GameEventType::NoPackWarning);
+            },
+            Some(_) if has_pack => {
+                self.log_progress("Pack reformed");
+                self.no_pack_since = None;
+                self.game_events.add(self.current_tick, GameEventType::PackReformed);
+            },
+            Some(no_pack_since) if self.current_tick - no_pack_since >= FAILURE_TO_REFORM_DURATION => {
+                self.no_pack_since = Some(self.current_tick);
+
+                // A pack needs blockers from both teams, so nobody fails to reform it while one team has none on track.
+                let blockers = Self::get_blocker_locations(jam_skaters);
+                let can_reform = [TeamSide::Home, TeamSide::Away].into_iter().all(|team| blockers.iter().any(|(t, _)| *t == team));
+
+                let is_reforming_blocker = |s: &&mut JamSkater| can_reform && s.position != Position::Jammer && matches!(s.activity, SkaterActivity::SkatingOnTrack(_));
+                let distance_from_pack = |s: &JamSkater| match s.activity {
+                    SkaterActivity::SkatingOnTrack(SkatingOnTrack { location }) => (location - pack.center()).abs(),
+                    _ => 0.0,
+                };
+
+                let furthest_blocker = jam_skaters.iter_mut()
+                    .flat_map(|(team, skaters)| skaters.iter_mut().map(move |s| (team, s)))
+                    .filter(|(_, s)| is_reforming_blocker(s))
+                    .max_by(|(_, a), (_, b)| distance_from_pack(a).total_cmp(&distance_from_pack(b)));
+
+                if let Some((team, skater)) = furthest_blocker {
+                    self.game_events.add(self.current_tick, GameEventType::FailureToReform {
+                        team,
+                        skater_id: skater.details.id,
+                        skater_name: skater.details.name.clone(),
+                    });
+                    skater.activity = self.give_skater_penalty_with_code(skater, team, penalty_code::ILLEGAL_POSITION.to_string());
+                }
+            },
+            _ => {},
+        }
+
+        if has_pack && self.random_source.gen_bool(PACK_SPLIT_CHANCE) {
+            let breakaway_team = if self.random_source.gen_bool(0.5) { TeamSide::Home } else { TeamSide::Away };
+            let breakaway_distance = self.random_source.gen_range(pack::PACK_PROXIMITY..pack::ENGAGEMENT_ZONE_LENGTH);
+
+            for skater in jam_skaters[breakaway_team].iter_mut().filter(|s| s.position != Position::Jammer) {
+                if let SkaterActivity::SkatingOnTrack(on_track) = &mut skater.activity {
+                    on_track.location += breakaway_distance;
+                }
+            }
+        }
+    }
+
+    fn get_random_star_pass(&mut self, jammer: &JamSkater, is_in_pack: bool, team: TeamSide) -> bool {
+        let team_jam = self.get_current_team_jam_mut(team);
+        let (has_star_passed, team_has_lead) = (team_jam.star_pass_tick.is_some(), team_jam.is_lead);
+
+        if jammer.is_lead || has_star_passed {
+            return false;
+        }
+
+        let opponent_has_lead = !self.lead_is_open && !team_has_lead;
+
+        let mut chance = STAR_PASS_CHANCE;
+        if is_in_pack {
+            chance *= STAR_PASS_IN_PACK_SCALE;
+        }
+        if opponent_has_lead {
+            chance *= STAR_PASS_OPPONENT_LEAD_SCALE;
+        }
+
+        self.random_source.gen_bool(chance)
+    }
+
+    fn complete_star_pass(&mut self, skaters: &mut OnTrackTeam, team: TeamSide) {
+        let Some(jammer_index) = skaters.iter().position(|s| s.is_passing_star) else {
+            return;
+        };
+        skaters[jammer_index].is_passing_star = false;
+
+        let jammer_is_on_track = matches!(skaters[jammer_index].activity, SkaterActivity::SkatingOnTrack(_));
+        let pivot_index = skaters.iter().position(|s| s.position == Position::Pivot && matches!(s.activity, SkaterActivity::SkatingOnTrack(_)));
+
+        let Some(pivot_index) = pivot_index.filter(|_| jammer_is_on_track) else {
+            return;
+        };
+
+        let star_pass_tick = self.get_random_current_tick();
+
+        let jammer_activity = skaters[jammer_index].activity.clone();
+        let pivot_activity = skaters[pivot_index].activity.clone();
+
+        let jammer = &mut skaters[jammer_index];
+        jammer.position = Position::Pivot;
+        jammer.activity = pivot_activity;
+        jammer.is_lead = false;
+        jammer.can_receive_lead = false;
+        let (jammer_id, jammer_name) = (jammer.details.id, jammer.details.name.clone());
+
+        let pivot = &mut skaters[pivot_index];
+        pivot.position = Position::Jammer;
+        pivot.activity = jammer_activity;
+        pivot.is_lead = false;
+        pivot.can_receive_lead = false;
+        let (pivot_id, pivot_name) = (pivot.details.id, pivot.details.name.clone());
+
+        self.get_current_team_jam_mut(team).star_pass(star_pass_tick);
+
+        self.log_progress(&format!("Star pass from {} to {}", jammer_name, pivot_name));
+        self.game_events.add(star_pass_tick, GameEventType::StarPassed {
+            team,
+            jammer_id,
+            jammer_name,
+            pivot_id,
+            pivot_name,
+        });
+    }
+
+    fn tick_skating_to_box_skater(&mut self, to_box: &SkatingToBox, skater: &mut JamSkater, team: TeamSide) -> SkaterActivity {
         let distance_covered = skater.details.base_speed + self.random_source.gen_range(-1.0..1.0);
         if to_box.distance_remaining > distance_covered {
-            let should_get_second_penalty = to_box.penalties_to_sit == 1 && self.random_source.gen_bool(1.0 / 20.0);
+            let is_eligible = self.teams[team].roster.iter().any(|s| s.details.id == skater.details.id && s.is_eligible());
+            let should_get_second_penalty = to_box.penalties_to_sit == 1 && is_eligible && self.random_source.gen_bool(1.0 / 20.0);
+
+            if should_get_second_penalty {
+                let code = self.penalty_code_weights.random_code(&mut self.random_source);
+                self.record_skater_penalty(skater, team, code);
+            }
 
             SkaterActivity::SkatingToBox(SkatingToBox {
                 distance_remaining: to_box.distance_remaining - distance_covered,
-                penalties_to_sit: if should_get_second_penalty { 2 } else { 1 },
+                penalties_to_sit: if should_get_second_penalty { 2 } else { to_box.penalties_to_sit },
             })
         } else {
+            let start_tick = self.get_random_current_tick();
+
+            self.game_json.start_box_trip(team, skater.details.id, start_tick, false);
+            self.game_events.add(start_tick, GameEventType::BoxEntered {
+                team,
+                skater_id: skater.details.id,
+                skater_name: skater.details.name.clone(),
+                penalty_count: to_box.penalties_to_sit,
+            });
+
             SkaterActivity::SatInBox(SatInBox {
-                start_tick: self.get_random_current_tick(),
+                start_tick,
                 penalty_count: to_box.penalties_to_sit,
             })
         }
     }
 
-    fn tick_sat_in_box_skater(&mut self, sat_in_box: &SatInBox, skater: &mut JamSkater) -> SkaterActivity {
+    fn tick_sat_in_box_skater(&mut self, sat_in_box: &SatInBox, skater: &mut JamSkater, team: TeamSide) -> SkaterActivity {
         let has_completed_penalties = self.current_tick - sat_in_box.start_tick > PENALTY_SIT_DURATION * sat_in_box.penalty_count as u64;
                 
         if has_completed_penalties {
-            println!("Releasing {}", skater.details.name);
+            self.log_progress(&format!("Releasing {}", skater.details.name));
             self.penalty_box.retain(|s| s.details.id != skater.details.id);
+            self.game_json.end_box_trip(skater.details.id, self.current_tick, false);
+
+            self.game_events.add(self.current_tick, GameEventType::BoxReleased {
+                team,
+                skater_id: skater.details.id,
+                skater_name: skater.details.name.clone(),
+            });
 
-            SkaterActivity::ReturningFromBox(ReturningFromBox { distance_remaining: self.random_source.gen_range(1.0..60.0) })
+            if skater.needs_substitute {
+                SkaterActivity::Removed
+            } else {
+                SkaterActivity::ReturningFromBox(ReturningFromBox { distance_remaining: self.random_source.gen_range(1.0..60.0) })
+            }
         } else {
             skater.activity.clone()
         }
     }
 
-    fn tick_returning_from_box_skater(&mut self, returning: &ReturningFromBox, skater: &mut JamSkater) -> SkaterActivity {
+    fn tick_returning_from_box_skater(&mut self, returning: &ReturningFromBox, skater: &mut JamSkater, pack: &Pack, team: TeamSide) -> SkaterActivity {
         let distance_covered = skater.details.base_speed + self.random_source.gen_range(-1.0..1.0);
         if returning.distance_remaining > distance_covered {
             SkaterActivity::ReturningFromBox(ReturningFromBox { distance_remaining: returning.distance_remaining - distance_covered })
@@ -508,10 +1107,10 @@ impl Game {
             let should_get_cut_penalty = self.random_source.gen_bool(RETURN_CUT_PENALTY_CHANCE);
 
             if should_get_cut_penalty {
-                self.give_skater_penalty(skater)
+                self.give_skater_penalty_with_code(skater, team, penalty_code::CUT.to_string())
             } else {
                 SkaterActivity::SkatingOnTrack(SkatingOnTrack { 
-                    location: 0.0
+                    location: pack.rear - self.random_source.gen_range(1.0..pack::PACK_PROXIMITY)
                 })
             }
         }
@@ -524,24 +1123,29 @@ impl Game {
         })
     }
 
-    fn tick_skater(&mut self, skater: &mut JamSkater, is_home_team: bool) {
+    fn tick_skater(&mut self, skater: &mut JamSkater, opponents: &OnTrackTeam, pack: &Pack, team: TeamSide) {
         skater.activity = match &skater.activity.clone() {
-            SkaterActivity::SkatingOnTrack(on_track) => self.tick_on_track_skater(on_track, skater, is_home_team),
-            SkaterActivity::SkatingToBox(to_box) => self.tick_skating_to_box_skater(to_box, skater),
-            SkaterActivity::SatInBox(sat_in_box) => self.tick_sat_in_box_skater(sat_in_box, skater),
-            SkaterActivity::ReturningFromBox(returning) => self.tick_returning_from_box_skater(returning, skater),
+            SkaterActivity::SkatingOnTrack(on_track) => self.tick_on_track_skater(on_track, skater, opponents, pack, team),
+            SkaterActivity::SkatingToBox(to_box) => self.tick_skating_to_box_skater(to_box, skater, team),
+            SkaterActivity::SatInBox(sat_in_box) => self.tick_sat_in_box_skater(sat_in_box, skater, team),
+            SkaterActivity::ReturningFromBox(returning) => self.tick_returning_from_box_skater(returning, skater, pack, team),
             SkaterActivity::HeldInBox(held_in_box) => self.tick_held_in_box_skater(held_in_box),
+            SkaterActivity::Removed => SkaterActivity::Removed,
         };
     }
 
     fn tick_lineup(&mut self, lineup: &LineupInProgress) -> GameState {
-        self.period_clock = if self.period_clock >= 1000 { self.period_clock - 1000 } else { 0 };
-        let period_has_expired = self.period_clock == 0;
-
-        if period_has_expired {
-            GameState::IntervalInProgress(IntervalInProgress { start_tick: self.get_random_current_tick() })
+        self.period_clock = self.period_clock.saturating_sub(1000);
+        let period_has_expired = self.period_clock == 0 && !self.is_overtime;
+
+        if period_has_expired && self.is_tied_after_regulation() {
+            self.start_overtime(self.current_tick)
+        } else if period_has_expired {
+            self.end_period(self.current_tick)
+        } else if let Some(timeout) = self.get_random_timeout() {
+            self.start_timeout(timeout)
         } else {
-            let should_start_new_jam = self.current_tick - lineup.start_tick >= 30000;
+            let should_start_new_jam = self.current_tick - lineup.start_tick >= LINEUP_DURATION;
 
             if should_start_new_jam {
                 self.start_jam()
@@ -551,19 +1155,128 @@ impl Game {
         }
     }
 
+    fn get_random_timeout(&mut self) -> Option<TimeoutInProgress> {
+        let start_tick = self.get_random_current_tick();
+
+        for team in [TeamSide::Home, TeamSide::Away] {
+            let (game_team, opposing_team) = (&self.teams[team], team.opponent());
+
+            let trailing_by = self.game_json.get_team_score(opposing_team) as f64 - self.game_json.get_team_score(team) as f64;
+            let timeout_chance = TEAM_TIMEOUT_CHANCE * (1.0 + trailing_by.max(0.0) / TEAM_TIMEOUT_TRAILING_SCORE_SCALE);
+
+            if game_team.timeouts_remaining > 0 && self.random_source.gen_bool(timeout_chance.min(1.0)) {
+                return Some(TimeoutInProgress {
+                    start_tick,
+                    duration: TEAM_TIMEOUT_DURATION,
+                    timeout_type: TimeoutType::Team,
+                    team: Some(team),
+                });
+            }
+
+            if game_team.has_official_review && self.random_source.gen_bool(OFFICIAL_REVIEW_CHANCE) {
+                return Some(TimeoutInProgress {
+                    start_tick,
+                    duration: self.random_source.gen_range(60..=180) * 1000,
+                    timeout_type: TimeoutType::Review,
+                    team: Some(team),
+                });
+            }
+        }
+
+        if self.random_source.gen_bool(OFFICIAL_TIMEOUT_CHANCE) {
+            return Some(TimeoutInProgress {
+                start_tick,
+                duration: self.random_source.gen_range(30..=180) * 1000,
+                timeout_type: TimeoutType::Official,
+                team: None,
+            });
+        }
+
+        None
+    }
+
+    fn start_timeout(&mut self, timeout: TimeoutInProgress) -> GameState {
+        self.log_progress(&format!("{:?} timeout called by {:?}", timeout.timeout_type, timeout.team));
+
+        if let (TimeoutType::Team, Some(team)) = (&timeout.timeout_type, timeout.team) {
+            self.teams[team].timeouts_remaining -= 1;
+        }
+
+        let period_clock_elapsed = self.get_period_clock_elapsed();
+        self.game_json.add_timeout(timeout.timeout_type, timeout.team, timeout.start_tick, period_clock_elapsed);
+        self.game_events.add(timeout.start_tick, GameEventType::TimeoutStarted { timeout_type: timeout.timeout_type, team: timeout.team });
+
+        GameState::TimeoutInProgress(timeout)
+    }
+
+    fn tick_timeout(&mut self, timeout: &TimeoutInProgress) -> GameState {
+        let has_timeout_ended = self.current_tick - timeout.start_tick >= timeout.duration;
+
+        if has_timeout_ended {
+            let end_tick = timeout.start_tick + timeout.duration;
+            let mut review_retained = false;
+
+            if let (TimeoutType::Review, Some(team)) = (&timeout.timeout_type, timeout.team) {
+                let can_retain = !self.teams[team].official_review_retained;
+                review_retained = can_retain && self.random_source.gen_bool(OFFICIAL_REVIEW_RETAINED_CHANCE);
+
+                let game_team = &mut self.teams[team];
+                if review_retained {
+                    game_team.official_review_retained = true;
+                } else {
+                    game_team.has_official_review = false;
+                }
+            }
+
+            self.game_json.end_timeout(end_tick, review_retained);
+            self.game_events.add(end_tick, GameEventType::TimeoutEnded { timeout_type: timeout.timeout_type, team: timeout.team, review_retained });
+
+            GameState::LineupInProgress(LineupInProgress { start_tick: end_tick })
+        } else {
+            GameState::TimeoutInProgress(timeout.clone())
+        }
+    }
+
+    fn tick_interval(&mut self, interval: &IntervalInProgress) -> GameState {
+        let should_start_new_period = self.current_tick - interval.start_tick >= INTERVAL_DURATION;
+
+        if should_start_new_period {
+            self.start_jam()
+        } else {
+            GameState::IntervalInProgress(interval.clone())
+        }
+    }
+
+    fn get_period_clock_elapsed(&self) -> u64 {
+        PERIOD_DURATION - self.period_clock
+    }
+
+    fn get_current_team_jam_mut(&mut self, team: TeamSide) -> &mut TeamJamJson {
+        &mut self.game_json.current_period_mut().unwrap().current_jam_mut().unwrap().team_jams[team]
+    }
+
+    fn log_progress(&self, message: &str) {
+        if self.verbose {
+            println!("{}", message);
+        }
+    }
+
     fn get_random_current_tick(&mut self) -> u64 {
         self.current_tick - self.random_source.gen_range(0..1000)
     }
 
-    fn get_random_jam_team(&mut self, team: &GameTeam) -> OnTrackTeam {
+    fn get_random_jam_team(&mut self, team_side: TeamSide, released_skater_ids: &mut Vec<Uuid>) -> OnTrackTeam {
         let mut on_track_skaters: Vec<JamSkater> =
             self.penalty_box.clone().into_iter()
-                .filter(|s| team.roster.iter().any(|r| r.details.id == s.details.id))
+                .filter(|s| self.teams[team_side].roster.iter().any(|r| r.details.id == s.details.id))
                 .map(|s| 
                     if s.position == Position::Jammer {
                         JamSkater {
                             can_receive_lead: true,
                             is_lead: false,
+                            is_passing_star: false,
+                            out_of_play_since: None,
+                            needs_substitute: s.needs_substitute,
                             activity: s.activity,
                             details: s.details,
                             position: s.position,
@@ -573,41 +1286,53 @@ impl Game {
                     })
                 .collect();
 
+        self.substitute_removed_skaters(&mut on_track_skaters, team_side);
+        self.release_unsubstituted_skaters(&mut on_track_skaters, team_side, released_skater_ids);
+
+        let team = self.teams[team_side].clone();
+
         let mut available_skaters: Vec<&GameSkater> =
             team.roster.iter()
+                .filter(|s| s.is_eligible())
                 .filter(|s| !on_track_skaters.iter().any(|r| r.details.id == s.details.id))
                 .collect();
 
-        available_skaters.sort_by(|a, b| a.last_jam_tick.cmp(&b.last_jam_tick));
+        available_skaters.sort_by_key(|s| s.last_jam_tick);
 
-        if !on_track_skaters.iter().any(|s| s.position == Position::Jammer) {
+        if !on_track_skaters.iter().any(|s| s.position == Position::Jammer) && !available_skaters.is_empty() {
             let mut available_jammers = available_skaters.clone();
             available_jammers.sort_by(Self::compare_preferences(&Self::get_position_jammer_value));
 
-            let index = self.random_source.gen_range(0..3);
+            let index = self.random_source.gen_range(0..available_jammers.len().min(3));
             let jammer = available_jammers[index];
             on_track_skaters.push(JamSkater {
                 details: jammer.details.clone(),
                 position: Position::Jammer,
-                activity: SkaterActivity::SkatingOnTrack(SkatingOnTrack { location: 95.0 }),
+                activity: SkaterActivity::SkatingOnTrack(SkatingOnTrack { location: self.random_source.gen_range(-5.0..0.0) }),
                 can_receive_lead: true,
                 is_lead: false,
+                is_passing_star: false,
+                out_of_play_since: None,
+                needs_substitute: false,
             });
             available_skaters.retain(|s| s.details.id != jammer.details.id);
         }
 
-        if !on_track_skaters.iter().any(|s| s.position == Position::Pivot) {
+        if !on_track_skaters.iter().any(|s| s.position == Position::Pivot) && !available_skaters.is_empty() {
             let mut available_pivots = available_skaters.clone();
             available_pivots.sort_by(Self::compare_preferences(&Self::get_position_pivot_value));
 
-            let index = self.random_source.gen_range(0..3);
+            let index = self.random_source.gen_range(0..available_pivots.len().min(3));
             let pivot = available_pivots[index];
             on_track_skaters.push(JamSkater {
                 details: pivot.details.clone(),
                 position: Position::Pivot,
-                activity: SkaterActivity::SkatingOnTrack(SkatingOnTrack { location: 0.0 }),
+                activity: SkaterActivity::SkatingOnTrack(SkatingOnTrack { location: self.random_source.gen_range(20.0..30.0) }),
                 can_receive_lead: false,
                 is_lead: false,
+                is_passing_star: false,
+                out_of_play_since: None,
+                needs_substitute: false,
             });
             available_skaters.retain(|s| s.details.id != pivot.details.id);
         }
@@ -615,20 +1340,23 @@ impl Game {
         let mut available_blockers = available_skaters.clone();
         available_blockers.sort_by(Self::compare_preferences(&Self::get_position_blocker_value));
 
-        while on_track_skaters.len() < 5 && available_blockers.len() > 0 {
-            let index = self.random_source.gen_range(0..3);
+        while on_track_skaters.len() < 5 && !available_blockers.is_empty() {
+            let index = self.random_source.gen_range(0..available_blockers.len().min(3));
             let blocker = available_blockers[index];
             on_track_skaters.push(JamSkater {
                 details: blocker.details.clone(),
                 position: Position::Blocker,
-                activity: SkaterActivity::SkatingOnTrack(SkatingOnTrack { location: 0.0 }),
+                activity: SkaterActivity::SkatingOnTrack(SkatingOnTrack { location: self.random_source.gen_range(5.0..25.0) }),
                 can_receive_lead: false,
                 is_lead: false,
+                is_passing_star: false,
+                out_of_play_since: None,
+                needs_substitute: false,
             });
             available_blockers.remove(index);
         }
 
-        return on_track_skaters;
+        on_track_skaters
     }
 
     fn compare_preferences(comparison: &'static dyn Fn(Position) -> u8) -> impl Fn(&&GameSkater, &&GameSkater) -> Ordering {
@@ -660,4 +1388,180 @@ impl Game {
             Position::Jammer => 0,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{game_json::TeamJamFielding, penalty_code, seeded_random_source, skater::Position, team_side::TeamSide, TeamPair};
+
+    use super::{Game, JamSkater, Penalty, SatInBox, SkaterActivity, SkatingOnTrack, SkatingToBox, FAILURE_TO_REFORM_DURATION, FOUL_OUT_PENALTY_COUNT, JAM_DURATION, PENALTY_SIT_DURATION, PERIOD_DURATION};
+
+    fn simulated_game(seed: u64) -> Game {
+        let mut game = Game::random(seeded_random_source(seed));
+        game.run();
+        game
+    }
+
+    fn blocker(game: &Game, team: TeamSide, index: usize, location: f32) -> JamSkater {
+        JamSkater {
+            details: game.teams[team].roster[index].details.clone(),
+            position: Position::Blocker,
+            activity: SkaterActivity::SkatingOnTrack(SkatingOnTrack { location }),
+            can_receive_lead: false,
+            is_lead: false,
+            is_passing_star: false,
+            out_of_play_since: None,
+            needs_substitute: false,
+        }
+    }
+
+    fn start_jam(game: &mut Game, skaters: &TeamPair<Vec<JamSkater>>) {
+        let id = game.game_json.new_id();
+        let fieldings = TeamPair::new(TeamJamFielding::from(skaters.home.clone()), TeamJamFielding::from(skaters.away.clone()));
+        game.game_json.add_period(0);
+        game.game_json.current_period_mut().unwrap().add_jam(id, 0, 0, &fieldings);
+    }
+
+    /// Ticks a game whose pack has been lost for long enough to penalize a blocker for failing to reform it.
+    fn tick_lost_pack(game: &mut Game, skaters: &mut TeamPair<Vec<JamSkater>>) {
+        start_jam(game, skaters);
+        game.no_pack_since = Some(0);
+        game.current_tick = FAILURE_TO_REFORM_DURATION;
+
+        let pack = Game::find_pack(skaters);
+        game.tick_pack(false, &pack, skaters);
+    }
+
+    #[test]
+    fn furthest_blocker_is_penalized_for_failing_to_reform() {
+        let mut game = Game::random(seeded_random_source(1));
+        let mut skaters = TeamPair::new(
+            vec![blocker(&game, TeamSide::Home, 0, 0.0), blocker(&game, TeamSide::Home, 1, 5.0)],
+            vec![blocker(&game, TeamSide::Away, 0, 30.0), blocker(&game, TeamSide::Away, 1, 60.0)],
+        );
+
+        tick_lost_pack(&mut game, &mut skaters);
+
+        assert!(matches!(skaters.away[1].activity, SkaterActivity::SkatingToBox(_)));
+        assert_eq!(game.game_json.penalties().len(), 1);
+        assert_eq!(game.game_json.penalties()[0].code, penalty_code::ILLEGAL_POSITION);
+    }
+
+    #[test]
+    fn nobody_fails_to_reform_without_blockers_from_both_teams() {
+        let mut game = Game::random(seeded_random_source(1));
+        let mut skaters = TeamPair::new(Vec::new(), vec![blocker(&game, TeamSide::Away, 0, 0.0), blocker(&game, TeamSide::Away, 1, 60.0)]);
+
+        tick_lost_pack(&mut game, &mut skaters);
+
+        assert!(skaters.away.iter().all(|s| matches!(s.activity, SkaterActivity::SkatingOnTrack(_))));
+        assert!(game.game_json.penalties().is_empty());
+    }
+
+    #[test]
+    fn removed_skaters_leave_the_track_after_serving_their_penalty() {
+        for needs_substitute in [false, true] {
+            let mut game = Game::random(seeded_random_source(1));
+            let mut skater = blocker(&game, TeamSide::Home, 0, 0.0);
+            skater.needs_substitute = needs_substitute;
+            start_jam(&mut game, &TeamPair::new(vec![skater.clone()], Vec::new()));
+            game.current_tick = PENALTY_SIT_DURATION + 1000;
+
+            let activity = game.tick_sat_in_box_skater(&SatInBox { start_tick: 0, penalty_count: 1 }, &mut skater, TeamSide::Home);
+
+            assert_eq!(matches!(activity, SkaterActivity::Removed), needs_substitute);
+        }
+    }
+
+    #[test]
+    fn removed_skaters_skating_to_the_box_get_no_more_penalties() {
+        let mut game = Game::random(seeded_random_source(1));
+        let mut skater = blocker(&game, TeamSide::Home, 0, 0.0);
+        start_jam(&mut game, &TeamPair::new(vec![skater.clone()], Vec::new()));
+        game.teams[TeamSide::Home].roster[0].penalties = vec![Penalty { code: "B".to_string(), received_tick: 0 }; FOUL_OUT_PENALTY_COUNT];
+
+        for _ in 0..200 {
+            game.tick_skating_to_box_skater(&SkatingToBox { distance_remaining: 1000.0, penalties_to_sit: 1 }, &mut skater, TeamSide::Home);
+        }
+
+        assert!(game.game_json.penalties().is_empty());
+    }
+
+    #[test]
+    fn unsubstituted_skaters_are_released_between_jams() {
+        let mut game = Game::random(seeded_random_source(1));
+        let mut skater = blocker(&game, TeamSide::Home, 0, 0.0);
+        skater.needs_substitute = true;
+        start_jam(&mut game, &TeamPair::new(vec![skater.clone()], Vec::new()));
+        game.game_json.start_box_trip(TeamSide::Home, skater.details.id, 0, false);
+        game.penalty_box.push(skater);
+        game.period_clock = PERIOD_DURATION;
+        game.current_tick = PENALTY_SIT_DURATION;
+        for game_skater in game.teams[TeamSide::Home].roster.iter_mut() {
+            game_skater.is_expelled = true;
+        }
+
+        game.start_jam();
+
+        let box_trip = &game.game_json.box_trips()[0];
+        assert!(box_trip.end_between_jams);
+        assert_eq!((box_trip.end_period_number, box_trip.end_jam_number), (1, 2));
+        assert!(box_trip.covers_jam(1, 1));
+        assert!(!box_trip.covers_jam(1, 2));
+    }
+
+    #[test]
+    fn jams_are_only_called_after_a_scoring_pass() {
+        for seed in [1, 2, 3] {
+            let game_json = simulated_game(seed).game_json;
+            let jams: Vec<_> = game_json.periods().iter().flat_map(|p| p.jams()).collect();
+
+            for jam in jams.iter() {
+                for (_, team_jam) in jam.team_jams.iter().filter(|(_, t)| t.called_off) {
+                    assert!(team_jam.trips().len() > 1, "Seed {}: jam called before a scoring pass", seed);
+                }
+            }
+
+            let called_count = jams.iter().filter(|j| j.team_jams.iter().any(|(_, t)| t.called_off)).count();
+            let full_count = jams.iter().filter(|j| j.end_tick - j.start_tick == JAM_DURATION).count();
+            assert!(called_count < jams.len(), "Seed {}: every jam was called", seed);
+            assert!(full_count * 5 >= jams.len(), "Seed {}: only {} of {} jams ran the full two minutes", seed, full_count, jams.len());
+        }
+    }
+
+    #[test]
+    fn removed_skaters_are_not_fielded_again() {
+        for seed in [1, 2, 3, 266] {
+            let game_json = simulated_game(seed).game_json;
+
+            for removal in game_json.removals() {
+                let later_jams = game_json.periods().iter().enumerate()
+                    .flat_map(|(p, period)| period.jams().iter().enumerate().map(move |(j, jam)| ((p + 1, j + 1), jam)))
+                    .filter(|(number, _)| *number > (removal.period_number, removal.jam_number));
+
+                for ((period_number, jam_number), jam) in later_jams {
+                    let fielding = jam.team_jam(removal.team).fielding();
+                    let is_fielded = [&fielding.jammer, &fielding.pivot, &fielding.blocker1, &fielding.blocker2, &fielding.blocker3].into_iter()
+                        .any(|s| s.as_ref().is_some_and(|s| s.skater_id == removal.skater_id));
+
+                    assert!(!is_fielded, "Seed {}: removed skater fielded in period {} jam {}", seed, period_number, jam_number);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn only_misconduct_penalties_expel() {
+        for seed in 1..=10 {
+            let game_json = simulated_game(seed).game_json;
+
+            for expulsion in game_json.removals().iter().filter(|r| r.code == penalty_code::EXPELLED) {
+                let expelling_penalty = game_json.penalties().iter()
+                    .rfind(|p| p.skater_id == expulsion.skater_id && p.tick <= expulsion.tick)
+                    .unwrap();
+
+                assert_eq!(expelling_penalty.code, penalty_code::MISCONDUCT, "Seed {}", seed);
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::team_side::TeamSide;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum TimeoutType {
+    Official,
+    Team,
+    Review,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum JamEndReason {
+    Called,
+    Expired,
+    Injury,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event")]
+pub enum GameEventType {
+    JamStarted {
+        period: usize,
+        jam: usize,
+    },
+    JamEnded {
+        period: usize,
+        jam: usize,
+        reason: JamEndReason,
+    },
+    LeadAwarded {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+    },
+    TripCompleted {
+        team: TeamSide,
+        trip: usize,
+        points: u8,
+    },
+    PenaltyIssued {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+        code: String,
+    },
+    BoxEntered {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+        penalty_count: u8,
+    },
+    BoxReleased {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+    },
+    StarPassed {
+        team: TeamSide,
+        jammer_id: Uuid,
+        jammer_name: String,
+        pivot_id: Uuid,
+        pivot_name: String,
+    },
+    FouledOut {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+    },
+    Expelled {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+    },
+    BoxSubstitution {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+        substitute_id: Uuid,
+        substitute_name: String,
+    },
+    NoPackWarning,
+    PackReformed,
+    FailureToReform {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+    },
+    OutOfPlayWarning {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+    },
+    FailureToReturn {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+    },
+    JamCalledOff {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+    },
+    LeadLost {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+    },
+    SkaterInjured {
+        team: TeamSide,
+        skater_id: Uuid,
+        skater_name: String,
+    },
+    TimeoutStarted {
+        timeout_type: TimeoutType,
+        team: Option<TeamSide>,
+    },
+    TimeoutEnded {
+        timeout_type: TimeoutType,
+        team: Option<TeamSide>,
+        review_retained: bool,
+    },
+    PeriodStarted {
+        period: usize,
+    },
+    PeriodEnded {
+        period: usize,
+    },
+    OvertimeStarted {
+        period: usize,
+    },
+    GameEnded,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameEvent {
+    pub tick: u64,
+    #[serde(flatten)]
+    pub event_type: GameEventType,
+}
+
+#[derive(Default)]
+pub struct GameEvents {
+    events: Vec<GameEvent>,
+}
+
+impl GameEvents {
+    pub fn new() -> GameEvents {
+        GameEvents {
+            events: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, tick: u64, event_type: GameEventType) {
+        self.events.push(GameEvent { tick, event_type });
+    }
+
+    /// Events in the order they were recorded, which may differ slightly from tick order.
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+}
+
+impl Display for GameEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Ticks are randomised within each second so events can be recorded slightly out of order
+        let mut events = self.events.clone();
+        events.sort_by_key(|e| e.tick);
+
+        let output = OutputYaml { events };
+
+        write!(f, "{}", serde_yaml::to_string(&output).unwrap())
+    }
+}
+
+#[derive(Serialize)]
+struct OutputYaml {
+    events: Vec<GameEvent>,
+}
@@ -1,18 +1,54 @@
-use chrono::{DateTime, Utc};
+use std::fmt::Display;
+
+use chrono::{Duration, NaiveDate};
 use serde::Serialize;
+use rand::{Rng, SeedableRng, rngs::StdRng};
 use serde_json::{json, Map};
 use uuid::Uuid;
 
-use crate::official::{Official, OfficialRole};
+mod crg_import;
+
+use crate::{official::{Official, OfficialRole}, game_events::TimeoutType, team_side::TeamSide, penalty_code, random_id, rules::{INTERVAL_DURATION, JAM_DURATION, PERIOD_DURATION, TEAM_TIMEOUT_COUNT}, team::Team, team_pair::TeamPair};
+
+pub const EVENT_VENUE: &str = "Example Sports Center";
+pub const EVENT_CITY: &str = "Testville";
+pub const EVENT_STATE: &str = "Testshire";
+pub const EVENT_HOST_LEAGUE: &str = "Test Roller Derby";
+pub const EVENT_START_TIME: &str = "12pm";
+pub const EVENT_GAME_NUMBER: &str = "1";
 
-const PERIOD_DURATION: u64 = 30 * 60 * 1000;
+/// Display colors for each uniform color name, as foreground and background.
+const TEAM_COLOR_VALUES: &[(&str, &str, &str)] = &[
+    ("Black", "#ffffff", "#000000"),
+    ("Gray", "#000000", "#808080"),
+    ("White", "#000000", "#ffffff"),
+    ("Red", "#ffffff", "#cc0000"),
+    ("Orange", "#000000", "#ff8c00"),
+    ("Brown", "#ffffff", "#8b4513"),
+    ("Yellow", "#000000", "#ffd700"),
+    ("Gold", "#000000", "#d4af37"),
+    ("Lime", "#000000", "#32cd32"),
+    ("Green", "#ffffff", "#008000"),
+    ("Turquoise", "#000000", "#40e0d0"),
+    ("Teal", "#ffffff", "#008080"),
+    ("Blue", "#ffffff", "#0000cc"),
+    ("Purple", "#ffffff", "#800080"),
+];
 
+#[derive(Clone)]
 pub struct TripJson {
     id: Uuid,
     pub after_star_pass: bool,
     pub start_tick: u64,
     pub duration: u64,
     pub score: u8,
+    pub is_complete: bool,
+}
+
+impl TripJson {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
 }
 
 #[derive(Clone)]
@@ -21,23 +57,56 @@ pub struct FieldingSkaterJson {
     pub number: String,
 }
 
+/// The skaters lined up in each position for a jam, with `None` for positions a short-handed team left empty.
 #[derive(Clone)]
 pub struct TeamJamFielding {
-    pub jammer: FieldingSkaterJson,
-    pub pivot: FieldingSkaterJson,
-    pub blocker1: FieldingSkaterJson,
-    pub blocker2: FieldingSkaterJson,
-    pub blocker3: FieldingSkaterJson,
+    pub jammer: Option<FieldingSkaterJson>,
+    pub pivot: Option<FieldingSkaterJson>,
+    pub blocker1: Option<FieldingSkaterJson>,
+    pub blocker2: Option<FieldingSkaterJson>,
+    pub blocker3: Option<FieldingSkaterJson>,
 }
 
+impl TeamJamFielding {
+    fn get_position_name(&self, skater_id: Uuid) -> Option<&'static str> {
+        [
+            (&self.blocker1, "blocker1"),
+            (&self.blocker2, "blocker2"),
+            (&self.blocker3, "blocker3"),
+            (&self.jammer, "jammer"),
+            (&self.pivot, "pivot"),
+        ].into_iter()
+            .find(|(s, _)| s.as_ref().is_some_and(|s| s.skater_id == skater_id))
+            .map(|(_, name)| name)
+    }
+}
+
+#[derive(Clone)]
 pub struct TeamJamJson {
     fielding: TeamJamFielding,
     pub called_off: bool,
     pub is_lead: bool,
+    pub lost_lead: bool,
+    pub injury: bool,
+    /// The skater injured in a jam called for injury, who must sit out the following jams.
+    pub injured_skater_id: Option<Uuid>,
+    pub star_pass_tick: Option<u64>,
     trips: Vec<TripJson>,
 }
 
 impl TeamJamJson {
+    pub fn fielding(&self) -> &TeamJamFielding {
+        &self.fielding
+    }
+
+    pub fn trips(&self) -> &[TripJson] {
+        &self.trips
+    }
+
+    pub(crate) fn trips_mut(&mut self) -> &mut [TripJson] {
+        &mut self.trips
+    }
+
     pub fn current_trip_mut(&mut self) -> Option<&mut TripJson> {
         self.trips.last_mut()
     }
@@ -46,25 +115,164 @@ impl TeamJamJson {
         self.trips.len()
     }
 
-    pub fn add_trip(&mut self, start_tick: u64) {
+    pub fn star_pass(&mut self, tick: u64) {
+        self.star_pass_tick = Some(tick);
+
+        if let Some(trip) = self.current_trip_mut() {
+            trip.after_star_pass = true;
+        }
+    }
+
+    /// Whether the jammer never completed their initial trip.
+    pub fn has_no_initial(&self) -> bool {
+        self.trips.first().is_none_or(|t| !t.is_complete)
+    }
+
+    pub fn jam_score(&self) -> u32 {
+        self.trips.iter().map(|t| t.score as u32).sum()
+    }
+
+    pub fn after_star_pass_score(&self) -> u32 {
+        self.trips.iter().filter(|t| t.after_star_pass).map(|t| t.score as u32).sum()
+    }
+
+    pub fn add_trip(&mut self, id: Uuid, start_tick: u64) {
         self.trips.push(TripJson { 
-            id: Uuid::new_v4(),
-            after_star_pass: false,
-            start_tick: start_tick,
+            id,
+            after_star_pass: self.star_pass_tick.is_some(),
+            start_tick,
             duration: 0,
             score: 0,
+            is_complete: false,
         });
     }
 }
 
+#[derive(Clone)]
+pub struct PenaltyJson {
+    id: Uuid,
+    pub team: TeamSide,
+    pub skater_id: Uuid,
+    pub code: String,
+    pub period_number: usize,
+    pub jam_number: usize,
+    pub tick: u64,
+    pub box_trip_id: Option<Uuid>,
+    pub substitute_id: Option<Uuid>,
+}
+
+impl PenaltyJson {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[derive(Clone)]
+pub struct BoxTripJson {
+    id: Uuid,
+    pub team: TeamSide,
+    pub skater_id: Uuid,
+    pub start_period_number: usize,
+    pub start_jam_number: usize,
+    pub start_between_jams: bool,
+    pub start_tick: u64,
+    pub end_period_number: usize,
+    pub end_jam_number: usize,
+    pub end_between_jams: bool,
+    pub end_tick: Option<u64>,
+}
+
+impl BoxTripJson {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub(crate) fn covers_jam(&self, period_number: usize, jam_number: usize) -> bool {
+        let jam = (period_number, jam_number);
+        let start = (self.start_period_number, self.start_jam_number);
+        let end = (self.end_period_number, self.end_jam_number);
+
+        let has_started = if self.start_between_jams { jam > start } else { jam >= start };
+        let has_not_ended = self.end_tick.is_none() || if self.end_between_jams { jam < end } else { jam <= end };
+
+        has_started && has_not_ended
+    }
+
+    pub(crate) fn started_in_jam(&self, period_number: usize, jam_number: usize) -> bool {
+        !self.start_between_jams && (self.start_period_number, self.start_jam_number) == (period_number, jam_number)
+    }
+
+    fn ended_in_jam(&self, period_number: usize, jam_number: usize) -> bool {
+        self.end_tick.is_some() && !self.end_between_jams && (self.end_period_number, self.end_jam_number) == (period_number, jam_number)
+    }
+
+    fn get_symbol(&self, period_number: usize, jam_number: usize) -> &'static str {
+        Self::format_symbol(self.started_in_jam(period_number, jam_number), self.ended_in_jam(period_number, jam_number))
+    }
+
+    fn get_star_pass_symbols(&self, period_number: usize, jam_number: usize, star_pass_tick: u64) -> (Option<&'static str>, Option<&'static str>) {
+        let started_in_jam = self.started_in_jam(period_number, jam_number);
+        let ended_in_jam = self.ended_in_jam(period_number, jam_number);
+        let started_after_star_pass = started_in_jam && self.start_tick > star_pass_tick;
+        let ended_before_star_pass = ended_in_jam && self.end_tick.is_some_and(|t| t <= star_pass_tick);
+
+        let before = if started_after_star_pass { None } else { Some(Self::format_symbol(started_in_jam, ended_before_star_pass)) };
+        let after = if ended_before_star_pass { None } else { Some(Self::format_symbol(started_after_star_pass, ended_in_jam)) };
+
+        (before, after)
+    }
+
+    fn format_symbol(started: bool, ended: bool) -> &'static str {
+        match (started, ended) {
+            (true, true) => "X",
+            (true, false) => "/",
+            (false, true) => "$",
+            (false, false) => "S",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TimeoutJson {
+    id: Uuid,
+    pub timeout_type: TimeoutType,
+    pub team: Option<TeamSide>,
+    pub review_retained: bool,
+    pub period_number: usize,
+    pub preceding_jam_number: usize,
+    pub start_tick: u64,
+    pub end_tick: Option<u64>,
+    pub period_clock_elapsed: u64,
+}
+
+impl TimeoutJson {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[derive(Clone)]
 pub struct JamJson {
     id: Uuid,
     pub start_tick: u64,
     pub end_tick: u64,
-    pub home_team_jam: TeamJamJson,
-    pub away_team_jam: TeamJamJson,
+    pub period_clock_elapsed_start: u64,
+    pub period_clock_elapsed_end: u64,
+    pub is_overtime: bool,
+    pub team_jams: TeamPair<TeamJamJson>,
 }
 
+impl JamJson {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn team_jam(&self, team: TeamSide) -> &TeamJamJson {
+        &self.team_jams[team]
+    }
+}
+
+#[derive(Clone)]
 pub struct PeriodJson {
     id: Uuid,
     pub start_tick: u64,
@@ -73,6 +281,22 @@ pub struct PeriodJson {
 }
 
 impl PeriodJson {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn jams(&self) -> &[JamJson] {
+        &self.jams
+    }
+
+    pub fn team_score(&self, team: TeamSide) -> u32 {
+        self.jams.iter().map(|j| j.team_jam(team).jam_score()).sum()
+    }
+
+    pub(crate) fn jams_mut(&mut self) -> &mut [JamJson] {
+        &mut self.jams
+    }
+
     pub fn current_jam_mut(&mut self) -> Option<&mut JamJson> {
         self.jams.last_mut()
     }
@@ -81,78 +305,357 @@ impl PeriodJson {
         self.jams.len()
     }
 
-    pub fn add_jam(&mut self, start_tick: u64, home_team_fielding: &TeamJamFielding, away_team_fielding: &TeamJamFielding) {
+    pub fn add_jam(&mut self, id: Uuid, start_tick: u64, period_clock_elapsed: u64, fieldings: &TeamPair<TeamJamFielding>) {
         self.jams.push(JamJson { 
-            id: Uuid::new_v4(),
-            start_tick: start_tick,
+            id,
+            start_tick,
             end_tick: 0,
-            home_team_jam: TeamJamJson { fielding: home_team_fielding.clone(), called_off: false, is_lead: false, trips: Vec::new() },
-            away_team_jam: TeamJamJson { fielding: away_team_fielding.clone(), called_off: false, is_lead: false, trips: Vec::new() },
+            period_clock_elapsed_start: period_clock_elapsed,
+            period_clock_elapsed_end: 0,
+            is_overtime: false,
+            team_jams: fieldings.map(|_, fielding| TeamJamJson { fielding: fielding.clone(), called_off: false, is_lead: false, lost_lead: false, injury: false, injured_skater_id: None, star_pass_tick: None, trips: Vec::new() }),
         });
     }
 }
 
 
+/// Game data accumulated during simulation, exported in CRG scoreboard JSON format.
+#[derive(Clone)]
 pub struct GameJson {
     id: Uuid,
+    id_source: StdRng,
+    clock_ids: [Uuid; 5],
+    first_jam_id: Uuid,
+    upcoming_jam_id: Uuid,
+    pub event_date: NaiveDate,
+    teams: Vec<(TeamSide, Team)>,
     officials_crew: Vec<Official>,
     periods: Vec<PeriodJson>,
+    penalties: Vec<PenaltyJson>,
+    removals: Vec<PenaltyJson>,
+    box_trips: Vec<BoxTripJson>,
+    timeouts: Vec<TimeoutJson>,
+    import_warnings: Vec<String>,
+}
+
+struct JamOutputContext<'a> {
+    jam: &'a JamJson,
+    period_number: usize,
+    jam_number: usize,
+    next_jam_id: Uuid,
+    previous_jam_id: Uuid,
 }
 
 impl GameJson {
-    pub fn new() -> GameJson {
+    pub fn new(random_source: &mut StdRng) -> GameJson {
+        let mut id_source = StdRng::seed_from_u64(random_source.gen());
+
         GameJson {
-            id: Uuid::new_v4(),
+            id: random_id::random_uuid(&mut id_source),
+            clock_ids: [(); 5].map(|_| random_id::random_uuid(&mut id_source)),
+            first_jam_id: random_id::random_uuid(&mut id_source),
+            upcoming_jam_id: random_id::random_uuid(&mut id_source),
+            event_date: Self::get_random_event_date(&mut id_source),
+            id_source,
+            teams: Vec::default(),
             officials_crew: Vec::default(),
             periods: Vec::default(),
+            penalties: Vec::default(),
+            removals: Vec::default(),
+            box_trips: Vec::default(),
+            timeouts: Vec::default(),
+            import_warnings: Vec::default(),
         }
     }
 
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn teams(&self) -> &[(TeamSide, Team)] {
+        &self.teams
+    }
+
+    pub fn officials(&self) -> &[Official] {
+        &self.officials_crew
+    }
+
+    pub fn periods(&self) -> &[PeriodJson] {
+        &self.periods
+    }
+
+    pub fn penalties(&self) -> &[PenaltyJson] {
+        &self.penalties
+    }
+
+    /// Foul-outs and expulsions, exported as each skater's `Penalty(0)`.
+    pub fn removals(&self) -> &[PenaltyJson] {
+        &self.removals
+    }
+
+    /// Problems found importing a CRG file that were worked around rather than rejecting the file.
+    pub fn import_warnings(&self) -> &[String] {
+        &self.import_warnings
+    }
+
+    pub fn box_trips(&self) -> &[BoxTripJson] {
+        &self.box_trips
+    }
+
+    pub fn timeouts(&self) -> &[TimeoutJson] {
+        &self.timeouts
+    }
+
+    pub fn new_id(&mut self) -> Uuid {
+        random_id::random_uuid(&mut self.id_source)
+    }
+
+    fn get_random_event_date(random_source: &mut StdRng) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 1, 1).unwrap() + Duration::days(random_source.gen_range(0..365))
+    }
+
+    pub(crate) fn periods_mut(&mut self) -> &mut [PeriodJson] {
+        &mut self.periods
+    }
+
+    pub(crate) fn penalties_mut(&mut self) -> &mut [PenaltyJson] {
+        &mut self.penalties
+    }
+
+    pub(crate) fn box_trips_mut(&mut self) -> &mut [BoxTripJson] {
+        &mut self.box_trips
+    }
+
     pub fn current_period_mut(&mut self) -> Option<&mut PeriodJson> {
         self.periods.last_mut()
     }
 
+    pub fn period_count(&self) -> usize {
+        self.periods.len()
+    }
+
     pub fn add_period(&mut self, start_tick: u64) {
+        let id = self.new_id();
         self.periods.push(PeriodJson { 
-            id: Uuid::new_v4(),
+            id,
             start_tick,
             duration: 0,
             jams: Vec::new(),
         });
     }
 
+    pub fn add_team(&mut self, team_side: TeamSide, team: &Team) {
+        self.teams.push((team_side, team.clone()));
+    }
+
     pub fn add_official(&mut self, official: &Official) {
         self.officials_crew.push(official.clone());
     }
 
-    pub fn to_string(&self) -> String {
+    pub fn add_penalty(&mut self, team: TeamSide, skater_id: Uuid, code: &str, tick: u64) {
+        let (period_number, jam_number) = self.current_jam_number();
+        let id = self.new_id();
+
+        self.penalties.push(PenaltyJson {
+            id,
+            team,
+            skater_id,
+            code: code.to_string(),
+            period_number,
+            jam_number,
+            tick,
+            box_trip_id: None,
+            substitute_id: None,
+        });
+    }
+
+    pub fn add_removal(&mut self, team: TeamSide, skater_id: Uuid, code: &str, tick: u64) {
+        let (period_number, jam_number) = self.current_jam_number();
+        let id = self.new_id();
+
+        self.removals.push(PenaltyJson {
+            id,
+            team,
+            skater_id,
+            code: code.to_string(),
+            period_number,
+            jam_number,
+            tick,
+            box_trip_id: None,
+            substitute_id: None,
+        });
+    }
+
+    pub fn substitute_penalty_server(&mut self, skater_id: Uuid, substitute_id: Uuid) {
+        for penalty in self.penalties.iter_mut().filter(|p| p.skater_id == skater_id && p.box_trip_id.is_none()) {
+            penalty.substitute_id = Some(substitute_id);
+        }
+
+        for box_trip in self.box_trips.iter_mut().filter(|t| t.skater_id == skater_id && t.end_tick.is_none()) {
+            box_trip.skater_id = substitute_id;
+        }
+    }
+
+    pub fn start_box_trip(&mut self, team: TeamSide, skater_id: Uuid, tick: u64, between_jams: bool) {
+        let (period_number, jam_number) = self.current_jam_number();
+        let id = self.new_id();
+
+        let is_served_by_skater = |p: &PenaltyJson| p.substitute_id.unwrap_or(p.skater_id) == skater_id;
+        for penalty in self.penalties.iter_mut().filter(|p| is_served_by_skater(p) && p.box_trip_id.is_none()) {
+            penalty.box_trip_id = Some(id);
+        }
+
+        self.box_trips.push(BoxTripJson {
+            id,
+            team,
+            skater_id,
+            start_period_number: period_number,
+            start_jam_number: jam_number,
+            start_between_jams: between_jams,
+            start_tick: tick,
+            end_period_number: 0,
+            end_jam_number: 0,
+            end_between_jams: false,
+            end_tick: None,
+        });
+    }
+
+    pub fn end_box_trip(&mut self, skater_id: Uuid, tick: u64, between_jams: bool) {
+        let (period_number, jam_number) = self.current_jam_number();
+
+        if let Some(box_trip) = self.box_trips.iter_mut().find(|t| t.skater_id == skater_id && t.end_tick.is_none()) {
+            box_trip.end_period_number = period_number;
+            box_trip.end_jam_number = jam_number;
+            box_trip.end_between_jams = between_jams;
+            box_trip.end_tick = Some(tick);
+        }
+    }
+
+    pub fn add_timeout(&mut self, timeout_type: TimeoutType, team: Option<TeamSide>, start_tick: u64, period_clock_elapsed: u64) {
+        let (period_number, preceding_jam_number) = self.current_jam_number();
+        let id = self.new_id();
+
+        self.timeouts.push(TimeoutJson {
+            id,
+            timeout_type,
+            team,
+            review_retained: false,
+            period_number,
+            preceding_jam_number,
+            start_tick,
+            end_tick: None,
+            period_clock_elapsed,
+        });
+    }
+
+    pub fn end_timeout(&mut self, end_tick: u64, review_retained: bool) {
+        if let Some(timeout) = self.timeouts.iter_mut().find(|t| t.end_tick.is_none()) {
+            timeout.end_tick = Some(end_tick);
+            timeout.review_retained = review_retained;
+        }
+    }
+
+    pub fn is_in_overtime(&self) -> bool {
+        self.periods.last().and_then(|p| p.jams.last()).is_some_and(|j| j.is_overtime)
+    }
+
+    pub fn get_team_score(&self, team: TeamSide) -> u32 {
+        self.periods.iter().map(|p| p.team_score(team)).sum()
+    }
+
+    fn get_team_score_before_jam(&self, team: TeamSide, period_number: usize, jam_number: usize) -> u32 {
+        self.periods.iter().enumerate()
+            .flat_map(|(p, period)| period.jams.iter().enumerate().map(move |(j, jam)| ((p + 1, j + 1), jam)))
+            .take_while(|(number, _)| *number < (period_number, jam_number))
+            .map(|(_, jam)| jam.team_jam(team).jam_score())
+            .sum()
+    }
+
+    fn current_jam_number(&self) -> (usize, usize) {
+        (self.periods.len(), self.periods.last().map(|p| p.jams.len()).unwrap_or(0))
+    }
+
+    fn get_jam(&self, period_number: usize, jam_number: usize) -> Option<&JamJson> {
+        self.periods.get(period_number.wrapping_sub(1))?.jams.get(jam_number.wrapping_sub(1))
+    }
+
+    fn get_fielding_id(&self, team: TeamSide, period_number: usize, jam_number: usize, skater_id: Uuid) -> Option<String> {
+        let jam = self.get_jam(period_number, jam_number)?;
+        let team_jam = jam.team_jam(team);
+        let position_name = team_jam.fielding.get_position_name(skater_id)?;
+
+        Some(Self::format_fielding_id(jam.id, team, position_name))
+    }
+
+    fn get_skater_box_trips(&self, team: TeamSide, period_number: usize, jam_number: usize, skater_id: Uuid) -> Vec<&BoxTripJson> {
+        self.box_trips.iter()
+            .filter(|t| t.team == team && t.skater_id == skater_id && t.covers_jam(period_number, jam_number))
+            .collect()
+    }
+
+    /// The lineup box trip symbols for a skater in a jam, split into those before and after their team's star pass.
+    /// All symbols are before the star pass when the team did not pass the star.
+    pub fn get_box_trip_symbols(&self, team: TeamSide, period_number: usize, jam_number: usize, skater_id: Uuid) -> (Vec<&'static str>, Vec<&'static str>) {
+        let box_trips = self.get_skater_box_trips(team, period_number, jam_number, skater_id);
+        let star_pass_tick = self.get_jam(period_number, jam_number).and_then(|j| j.team_jam(team).star_pass_tick);
+
+        match star_pass_tick {
+            Some(star_pass_tick) => {
+                let symbols: Vec<_> = box_trips.iter().map(|t| t.get_star_pass_symbols(period_number, jam_number, star_pass_tick)).collect();
+
+                (
+                    symbols.iter().filter_map(|(b, _)| *b).collect(),
+                    symbols.iter().filter_map(|(_, a)| *a).collect(),
+                )
+            },
+            None => (box_trips.iter().map(|t| t.get_symbol(period_number, jam_number)).collect(), Vec::new()),
+        }
+    }
+
+    fn format_fielding_id(jam_id: Uuid, team: TeamSide, position_name: &str) -> String {
+        format!("{}_{}_{}", jam_id.as_hyphenated(), team.number(), position_name)
+    }
+
+    fn get_suspensions_served(&self) -> String {
+        self.removals.iter()
+            .filter(|r| r.code == penalty_code::EXPELLED)
+            .filter_map(|r| {
+                let (_, team) = self.teams.iter().find(|(side, _)| *side == r.team)?;
+                let skater = team.roster.iter().find(|s| s.id == r.skater_id)?;
+
+                Some(format!("{} #{}", team.name, skater.number))
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    fn to_output_json(&self) -> OutputJson {
         let mut output = OutputJson { state: Map::new() };
 
-        let key_prefix = format!("ScoreBoard.Game({})", Uuid::new_v4().as_hyphenated().to_string());
+        let key_prefix = format!("ScoreBoard.Game({})", self.id.as_hyphenated());
         let key = |k: &str| format!("{}.{}", key_prefix, k);
 
         output.state.insert(key("AbortReason"), json!(""));
         self.output_clocks(&key_prefix, &mut output);
         output.state.insert(key("ClockDuringFinalScore"), json!(false));
-        output.state.insert(key("CurrentPeriod"), json!(self.periods.iter().last().unwrap().id.as_hyphenated().to_string()));
+        output.state.insert(key("CurrentPeriod"), json!(self.periods.last().map(|p| p.id.as_hyphenated().to_string()).unwrap_or_default()));
         output.state.insert(key("CurrentPeriodNumber"), json!(self.periods.len()));
         output.state.insert(key("CurrentTimeout"), json!("noTimeout"));
-        output.state.insert(key("EventInfo(City)"), json!("Testville"));
-        let datetime: DateTime<Utc> = std::time::SystemTime::now().into();
-        output.state.insert(key("EventInfo(Date)"), json!(datetime.format("%Y-%m-%d").to_string()));
-        output.state.insert(key("EventInfo(GameNo)"), json!("1"));
-        output.state.insert(key("EventInfo(HostLeague)"), json!("Test Roller Derby"));
-        output.state.insert(key("EventInfo(StartTime)"), json!("12pm"));
-        output.state.insert(key("EventInfo(State)"), json!("Testshire"));
+        output.state.insert(key("EventInfo(City)"), json!(EVENT_CITY));
+        output.state.insert(key("EventInfo(Date)"), json!(self.event_date.format("%Y-%m-%d").to_string()));
+        output.state.insert(key("EventInfo(GameNo)"), json!(EVENT_GAME_NUMBER));
+        output.state.insert(key("EventInfo(HostLeague)"), json!(EVENT_HOST_LEAGUE));
+        output.state.insert(key("EventInfo(StartTime)"), json!(EVENT_START_TIME));
+        output.state.insert(key("EventInfo(State)"), json!(EVENT_STATE));
         output.state.insert(key("EventInfo(Tournament)"), json!(""));
-        output.state.insert(key("EventInfo(Venue)"), json!("Example Sports Center"));
+        output.state.insert(key("EventInfo(Venue)"), json!(EVENT_VENUE));
         output.state.insert(key("ExportBlockedBy"), json!(""));
         output.state.insert(key("Filename"), json!("STATS-Test"));
-        output.state.insert(key("HNSO"), json!(self.officials_crew.iter().filter(|o| o.is_head && o.role != OfficialRole::InsidePackReferee).next().unwrap().name));
-        output.state.insert(key("HR"), json!(self.officials_crew.iter().filter(|o| o.is_head && o.role == OfficialRole::InsidePackReferee).next().unwrap().name));
+        output.state.insert(key("HNSO"), json!(self.officials_crew.iter().find(|o| o.is_head && !o.role.is_referee()).map(|o| o.name.clone()).unwrap_or_default()));
+        output.state.insert(key("HR"), json!(self.officials_crew.iter().find(|o| o.is_head && o.role.is_referee()).map(|o| o.name.clone()).unwrap_or_default()));
         output.state.insert(key("Id"), json!(self.id.as_hyphenated().to_string()));
         output.state.insert(key("InJam"), json!(false));
-        output.state.insert(key("InOvertime"), json!(false));
+        output.state.insert(key("InOvertime"), json!(self.is_in_overtime()));
         output.state.insert(key("InPeriod"), json!(false));
         output.state.insert(key("InSuddenScoring"), json!(false));
         output.state.insert(key("InjuryContinuationUpcoming"), json!(false));
@@ -186,12 +689,12 @@ impl GameJson {
         output.state.insert(key("Readonly"), json!(false));
         output.state.insert(key("State"), json!("Finished"));
         output.state.insert(key("StatsbookExists"), json!(false));
-        output.state.insert(key("SuspensionsServed"), json!(""));
+        output.state.insert(key("SuspensionsServed"), json!(self.get_suspensions_served()));
 
         output.state.insert("ScoreBoard.Version(release)".to_string(), json!("v2023.3"));
 
-        let first_jam_id = Uuid::new_v4();
-        let upcoming_jam_id = Uuid::new_v4();
+        let first_jam_id = self.first_jam_id;
+        let upcoming_jam_id = self.upcoming_jam_id;
 
         let mut period_number = 0;
         for period in self.periods.iter() {
@@ -200,66 +703,77 @@ impl GameJson {
             let period_key_prefix = key(format!("Period({})", period_number).as_str());
             let key = |k: &str| format!("{}.{}", period_key_prefix, k);
 
-            output.state.insert(key("CurrentJam"), json!(period.jams.last().unwrap().id.as_hyphenated().to_string()));
+            output.state.insert(key("CurrentJam"), json!(period.jams.last().map(|j| j.id.as_hyphenated().to_string()).unwrap_or_default()));
             output.state.insert(key("CurrentJamNumber"), json!(period.jams.len()));
             output.state.insert(key("Duration"), json!(period.duration));
-            output.state.insert(key("FirstJam"), json!(period.jams[0].id.as_hyphenated().to_string()));
+            output.state.insert(key("FirstJam"), json!(period.jams.first().map(|j| j.id.as_hyphenated().to_string()).unwrap_or_default()));
             output.state.insert(key("FirstJamNumber"), json!(1));
             output.state.insert(key("id"), json!(period.id.as_hyphenated().to_string()));
+            output.state.insert(key("WalltimeEnd"), json!(period.start_tick + period.duration));
+            output.state.insert(key("WalltimeStart"), json!(period.start_tick));
+
+            for team in [TeamSide::Home, TeamSide::Away] {
+                let penalty_count = self.penalties.iter().filter(|p| p.team == team && p.period_number == period_number).count();
+
+                output.state.insert(key(format!("Team{}PenaltyCount", team.number()).as_str()), json!(penalty_count));
+                output.state.insert(key(format!("Team{}Points", team.number()).as_str()), json!(period.team_score(team)));
+            }
 
             let mut jam_number = 0;
             for jam in period.jams.iter() {
                 jam_number += 1;
 
-                let next_jam_id = if jam_number < period.jams.len() {
-                    period.jams[jam_number].id
-                } else {
-                    if period_number < self.periods.len() {
-                        self.periods[period_number].jams[0].id
-                    } else {
-                        upcoming_jam_id
-                    }
-                };
-
-                let previous_jam_id = if jam_number > 1 {
-                    period.jams[jam_number - 2].id
-                } else {
-                    if period_number > 1 {
-                        self.periods[period_number - 2].jams.last().unwrap().id
-                    } else {
-                        first_jam_id
-                    }
-                };
+                let next_jam_id = period.jams.get(jam_number)
+                    .or_else(|| self.periods[period_number..].iter().find_map(|p| p.jams.first()))
+                    .map(|j| j.id)
+                    .unwrap_or(upcoming_jam_id);
+
+                let previous_jam_id = period.jams[..jam_number - 1].last()
+                    .or_else(|| self.periods[..period_number - 1].iter().rev().find_map(|p| p.jams.last()))
+                    .map(|j| j.id)
+                    .unwrap_or(first_jam_id);
 
                 let jam_key_prefix = key(format!("Jam({})", jam_number).as_str());
                 let key = |k: &str| format!("{}.{}", jam_key_prefix, k);
 
-                output.state.insert(key("Duration"), json!(jam.end_tick - jam.start_tick));
+                output.state.insert(key("Duration"), json!(jam.end_tick.saturating_sub(jam.start_tick)));
                 output.state.insert(key("Id"), json!(jam.id.as_hyphenated().to_string()));
                 output.state.insert(key("InjuryContinuation"), json!(false));
                 output.state.insert(key("Next"), json!(next_jam_id.as_hyphenated().to_string()));
                 output.state.insert(key("Number"), json!(jam_number));
-                output.state.insert(key("Overtime"), json!(false));
-                output.state.insert(key("PeriodClockDisplayEnd"), json!(PERIOD_DURATION - (jam.end_tick - period.start_tick)));
-                output.state.insert(key("PeriodClockElapsedEnd"), json!(jam.end_tick - period.start_tick));
-                output.state.insert(key("PeriodClockElapsedStart"), json!(jam.start_tick - period.start_tick));
+                output.state.insert(key("Overtime"), json!(jam.is_overtime));
+                output.state.insert(key("PeriodClockDisplayEnd"), json!(PERIOD_DURATION - jam.period_clock_elapsed_end));
+                output.state.insert(key("PeriodClockElapsedEnd"), json!(jam.period_clock_elapsed_end));
+                output.state.insert(key("PeriodClockElapsedStart"), json!(jam.period_clock_elapsed_start));
                 output.state.insert(key("PeriodNumber"), json!(period_number));
                 output.state.insert(key("Previous"), json!(previous_jam_id.as_hyphenated().to_string()));
                 output.state.insert(key("Readonly"), json!(false));
-                output.state.insert(key("StarPass"), json!(false));
+                output.state.insert(key("StarPass"), json!(jam.team_jams.iter().any(|(_, t)| t.star_pass_tick.is_some())));
+                output.state.insert(key("WalltimeEnd"), json!(jam.end_tick));
+                output.state.insert(key("WalltimeStart"), json!(jam.start_tick));
 
-                self.output_team(&jam, jam_number, &jam.home_team_jam, &format!("{}.TeamJam(1)", jam_key_prefix), next_jam_id, previous_jam_id, &mut output);
-                self.output_team(&jam, jam_number, &jam.away_team_jam, &format!("{}.TeamJam(2)", jam_key_prefix), next_jam_id, previous_jam_id, &mut output);
+                let context = JamOutputContext { jam, period_number, jam_number, next_jam_id, previous_jam_id };
+
+                for (team, team_jam) in jam.team_jams.iter() {
+                    self.output_team(&context, team, team_jam, &format!("{}.TeamJam({})", jam_key_prefix, team.number()), &mut output);
+                }
             }
         }
-        serde_json::to_string_pretty(&output).unwrap()
+
+        self.output_teams(&key_prefix, &mut output);
+        self.output_officials(&key_prefix, &mut output);
+        self.output_timeouts(&key_prefix, &mut output);
+        self.output_penalties(&key_prefix, &mut output);
+        self.output_box_trips(&key_prefix, &mut output);
+
+        output
     }
 
     fn output_clocks(&self, key_prefix: &String, output: &mut OutputJson) {
-        let mut output_clock = |name: &str, direction: bool, max_time: u64| {
+        let mut output_clock = |name: &str, id: Uuid, direction: bool, max_time: u64| {
             let key = |k: &str| format!("{}.Clock({}).{}", key_prefix, name, k);
             output.state.insert(key("Direction"), json!(direction));
-            output.state.insert(key("Id"), json!(Uuid::new_v4().as_hyphenated().to_string()));
+            output.state.insert(key("Id"), json!(id.as_hyphenated().to_string()));
             output.state.insert(key("InvertedTime"), json!(max_time));
             output.state.insert(key("MaximumTime"), json!(max_time));
             output.state.insert(key("Name"), json!(name));
@@ -269,53 +783,82 @@ impl GameJson {
             output.state.insert(key("Time"), json!(0));
         };
 
-        output_clock("Intermission", true, 3600000);
-        output_clock("Jam", true, 120000);
-        output_clock("Lineup", false, 86400000);
-        output_clock("Period", true, 1200000);
-        output_clock("Timeout", false, 86400000);
+        output_clock("Intermission", self.clock_ids[0], true, INTERVAL_DURATION);
+        output_clock("Jam", self.clock_ids[1], true, JAM_DURATION);
+        output_clock("Lineup", self.clock_ids[2], false, 86400000);
+        output_clock("Period", self.clock_ids[3], true, PERIOD_DURATION);
+        output_clock("Timeout", self.clock_ids[4], false, 86400000);
     }
 
-    fn output_team(&self, jam: &JamJson, jam_number: usize, team_jam: &TeamJamJson, jam_key_prefix: &String, next_jam_id: Uuid, previous_jam_id: Uuid, output: &mut OutputJson) {
+    fn output_team(&self, context: &JamOutputContext, team: TeamSide, team_jam: &TeamJamJson, jam_key_prefix: &String, output: &mut OutputJson) {
         let key = |k: &str| format!("{}.{}", jam_key_prefix, k);
 
-        output.state.insert(key("AfterSPScore"), json!(0));
+        output.state.insert(key("AfterSPScore"), json!(team_jam.after_star_pass_score()));
         output.state.insert(key("Calloff"), json!(team_jam.called_off));
-        output.state.insert(key("CurrentTrip"), json!(team_jam.trips.last().unwrap().id.as_hyphenated().to_string()));
+        output.state.insert(key("CurrentTrip"), json!(team_jam.trips.last().map(|t| t.id.as_hyphenated().to_string()).unwrap_or_default()));
         output.state.insert(key("CurrentTripNumber"), json!(team_jam.trips.len()));
-        output.state.insert(key("DisplayLead"), json!(team_jam.is_lead));
+        output.state.insert(key("DisplayLead"), json!(team_jam.is_lead && !team_jam.lost_lead));
+        output.state.insert(key("Injury"), json!(team_jam.injury));
+        output.state.insert(key("Lead"), json!(team_jam.is_lead));
+        output.state.insert(key("LeadJammer"), json!(match (team_jam.is_lead, team_jam.lost_lead) {
+            (true, false) => "Lead",
+            (true, true) => "LostLead",
+            (false, _) => "NoLead",
+        }));
+        output.state.insert(key("Lost"), json!(team_jam.lost_lead));
+        output.state.insert(key("NoInitial"), json!(team_jam.has_no_initial()));
+
+        let last_score = self.get_team_score_before_jam(team, context.period_number, context.jam_number);
+        output.state.insert(key("JamScore"), json!(team_jam.jam_score()));
+        output.state.insert(key("LastScore"), json!(last_score));
+        output.state.insert(key("OsOffset"), json!(0));
+        output.state.insert(key("TotalScore"), json!(last_score + team_jam.jam_score()));
+        output.state.insert(key("StarPass"), json!(team_jam.star_pass_tick.is_some()));
+        output.state.insert(key("StarPassTrip"), json!(team_jam.trips.iter().find(|t| t.after_star_pass).map(|t| t.id.as_hyphenated().to_string()).unwrap_or_default()));
 
-        self.output_team_jam_roster(jam, jam_number, team_jam, jam_key_prefix, next_jam_id, previous_jam_id, output);
-        self.output_team_trips(jam, team_jam, jam_key_prefix, output);
+        self.output_team_jam_roster(context, team, team_jam, jam_key_prefix, output);
+        self.output_team_trips(context.jam, team_jam, jam_key_prefix, output);
     }
 
-    fn output_team_jam_roster(&self, jam: &JamJson, jam_number: usize, team_jam: &TeamJamJson, key_prefix: &String, next_jam_id: Uuid, previous_jam_id: Uuid, output: &mut OutputJson) {
-        self.output_skater(jam, jam_number, &team_jam.fielding.blocker1, &format!("{}.Fielding(Blocker1)", key_prefix), "blocker1", next_jam_id, previous_jam_id, output);
-        self.output_skater(jam, jam_number, &team_jam.fielding.blocker2, &format!("{}.Fielding(Blocker2)", key_prefix), "blocker2", next_jam_id, previous_jam_id, output);
-        self.output_skater(jam, jam_number, &team_jam.fielding.blocker3, &format!("{}.Fielding(Blocker3)", key_prefix), "blocker3", next_jam_id, previous_jam_id, output);
-        self.output_skater(jam, jam_number, &team_jam.fielding.jammer, &format!("{}.Fielding(Jammer)", key_prefix), "jammer", next_jam_id, previous_jam_id, output);
-        self.output_skater(jam, jam_number, &team_jam.fielding.pivot, &format!("{}.Fielding(Pivot)", key_prefix), "pivot", next_jam_id, previous_jam_id, output);
+    fn output_team_jam_roster(&self, context: &JamOutputContext, team: TeamSide, team_jam: &TeamJamJson, key_prefix: &String, output: &mut OutputJson) {
+        self.output_skater(context, team, &team_jam.fielding.blocker1, &format!("{}.Fielding(Blocker1)", key_prefix), "blocker1", output);
+        self.output_skater(context, team, &team_jam.fielding.blocker2, &format!("{}.Fielding(Blocker2)", key_prefix), "blocker2", output);
+        self.output_skater(context, team, &team_jam.fielding.blocker3, &format!("{}.Fielding(Blocker3)", key_prefix), "blocker3", output);
+        self.output_skater(context, team, &team_jam.fielding.jammer, &format!("{}.Fielding(Jammer)", key_prefix), "jammer", output);
+        self.output_skater(context, team, &team_jam.fielding.pivot, &format!("{}.Fielding(Pivot)", key_prefix), "pivot", output);
     }
 
-    fn output_skater(&self, jam: &JamJson, jam_number: usize, skater: &FieldingSkaterJson, key_prefix: &String, position_name: &str, next_jam_id: Uuid, previous_jam_id: Uuid, output: &mut OutputJson) {
+    fn output_skater(&self, context: &JamOutputContext, team: TeamSide, skater: &Option<FieldingSkaterJson>, key_prefix: &String, position_name: &str, output: &mut OutputJson) {
         let key = |k: &str| format!("{}.{}", key_prefix, k);
 
+        let box_trips = skater.as_ref().map(|s| self.get_skater_box_trips(team, context.period_number, context.jam_number, s.skater_id)).unwrap_or_default();
+        let box_trip_symbols: Vec<&str> = box_trips.iter().map(|t| t.get_symbol(context.period_number, context.jam_number)).collect();
+        let current_box_trip = box_trips.iter().find(|t| !t.ended_in_jam(context.period_number, context.jam_number));
+
+        let team_jam = context.jam.team_jam(team);
+        let (symbols_before_star_pass, symbols_after_star_pass) = match skater {
+            Some(skater) => self.get_box_trip_symbols(team, context.period_number, context.jam_number, skater.skater_id),
+            None => (Vec::new(), Vec::new()),
+        };
+        let symbols_before_star_pass = symbols_before_star_pass.join(" ");
+        let symbols_after_star_pass = symbols_after_star_pass.join(" ");
+
         output.state.insert(key("Annotation"), json!(""));
-        output.state.insert(key("BoxTripSymbols"), json!(""));
-        output.state.insert(key("BoxTripSymbolsAfterSP"), json!(""));
-        output.state.insert(key("BoxTripSymbolsBeforeSP"), json!(""));
-        output.state.insert(key("CurrentBoxTrip"), json!(""));
-        output.state.insert(key("Id"), json!(format!("{}_1_{}", jam.id.as_hyphenated().to_string(), position_name)));
-        output.state.insert(key("Next"), json!(format!("{}_1_{}", next_jam_id.as_hyphenated().to_string(), position_name)));
-        output.state.insert(key("NotFielded"), json!(false));
-        output.state.insert(key("Number"), json!(jam_number));
-        output.state.insert(key("PenaltyBox"), json!(false));
-        output.state.insert(key("Position"), json!(format!("00000000-0000-0000-0000-000000000000_1_{}", position_name)));
-        output.state.insert(key("Next"), json!(format!("{}_1_{}", previous_jam_id.as_hyphenated().to_string(), position_name)));
+        output.state.insert(key("BoxTripSymbols"), json!(box_trip_symbols.join(" ")));
+        output.state.insert(key("BoxTripSymbolsAfterSP"), json!(symbols_after_star_pass));
+        output.state.insert(key("BoxTripSymbolsBeforeSP"), json!(symbols_before_star_pass));
+        output.state.insert(key("CurrentBoxTrip"), json!(current_box_trip.map(|t| t.id.as_hyphenated().to_string()).unwrap_or_default()));
+        output.state.insert(key("Id"), json!(Self::format_fielding_id(context.jam.id, team, position_name)));
+        output.state.insert(key("Next"), json!(Self::format_fielding_id(context.next_jam_id, team, position_name)));
+        output.state.insert(key("NotFielded"), json!(skater.is_none()));
+        output.state.insert(key("Number"), json!(context.jam_number));
+        output.state.insert(key("PenaltyBox"), json!(current_box_trip.is_some()));
+        output.state.insert(key("Position"), json!(format!("00000000-0000-0000-0000-000000000000_{}_{}", team.number(), position_name)));
+        output.state.insert(key("Previous"), json!(Self::format_fielding_id(context.previous_jam_id, team, position_name)));
         output.state.insert(key("Readonly"), json!(false));
-        output.state.insert(key("SitFor3"), json!(false));
-        output.state.insert(key("Skater"), json!(skater.skater_id.as_hyphenated().to_string()));
-        output.state.insert(key("SkaterNumber"), json!(skater.number));
+        output.state.insert(key("SitFor3"), json!(team_jam.injured_skater_id.is_some_and(|id| skater.as_ref().is_some_and(|s| s.skater_id == id))));
+        output.state.insert(key("Skater"), json!(skater.as_ref().map(|s| s.skater_id.as_hyphenated().to_string()).unwrap_or_default()));
+        output.state.insert(key("SkaterNumber"), json!(skater.as_ref().map(|s| s.number.clone()).unwrap_or_default()));
     }
 
     fn output_team_trips(&self, jam: &JamJson, team_jam: &TeamJamJson, jam_key_prefix: &String, output: &mut OutputJson) {
@@ -327,19 +870,322 @@ impl GameJson {
 
             let key = |k: &str| format!("{}.{}", trip_key_prefix, k);
 
+            output.state.insert(key("AfterSP"), json!(trip.after_star_pass));
             output.state.insert(key("Current"), json!(false));
             output.state.insert(key("Duration"), json!(trip.duration));
             output.state.insert(key("Id"), json!(trip.id.as_hyphenated().to_string()));
-            output.state.insert(key("JamClockStart"), json!(trip.start_tick - jam.start_tick));
-            output.state.insert(key("JamClockEnd"), json!(trip.start_tick + trip.duration - jam.start_tick));
+            output.state.insert(key("JamClockStart"), json!(trip.start_tick.saturating_sub(jam.start_tick)));
+            output.state.insert(key("JamClockEnd"), json!((trip.start_tick + trip.duration).saturating_sub(jam.start_tick)));
             output.state.insert(key("Number"), json!(trip_number));
             output.state.insert(key("Readonly"), json!(false));
             output.state.insert(key("Score"), json!(trip.score));
         }
     }
+
+    fn output_officials(&self, key_prefix: &String, output: &mut OutputJson) {
+        for official in self.officials_crew.iter() {
+            let official_type = if official.role.is_referee() { "Ref" } else { "Nso" };
+            let official_key_prefix = format!("{}.{}({})", key_prefix, official_type, official.id.as_hyphenated());
+            let key = |k: &str| format!("{}.{}", official_key_prefix, k);
+            let role = match (official.is_head, official.role.is_referee()) {
+                (true, true) => "Head Referee",
+                (true, false) => "Head Non-Skating Official",
+                (false, _) => Self::format_official_role(official.role),
+            };
+
+            output.state.insert(key("Cert"), json!(official.certification.clone().unwrap_or_default()));
+            output.state.insert(key("Id"), json!(official.id.as_hyphenated().to_string()));
+            output.state.insert(key("League"), json!(official.league.clone().unwrap_or_default()));
+            output.state.insert(key("Name"), json!(official.name));
+            output.state.insert(key("P1Team"), json!(official.team.map(|t| format!("{}_{}", self.id.as_hyphenated(), t.number())).unwrap_or_default()));
+            output.state.insert(key("Readonly"), json!(false));
+            output.state.insert(key("Role"), json!(role));
+            output.state.insert(key("Swap"), json!(false));
+        }
+    }
+
+    pub fn format_official_role(role: OfficialRole) -> &'static str {
+        match role {
+            OfficialRole::PenaltyLineupTracker => "Penalty Lineup Tracker",
+            OfficialRole::PenaltyWrangler => "Penalty Wrangler",
+            OfficialRole::InsideWhiteboard => "Inside Whiteboard Operator",
+            OfficialRole::JamTimer => "Jam Timer",
+            OfficialRole::Scorekeeper => "Scorekeeper",
+            OfficialRole::ScoreboardOperator => "ScoreBoard Operator",
+            OfficialRole::PenaltyBoxManager => "Penalty Box Manager",
+            OfficialRole::PenaltyBoxTimer => "Penalty Box Timer",
+            OfficialRole::InsidePackReferee => "Inside Pack Referee",
+            OfficialRole::OutsidePackReferee => "Outside Pack Referee",
+            OfficialRole::JammerReferee => "Jammer Referee",
+            OfficialRole::NonSkatingOfficialAlternate => "Non-Skating Official Alternate",
+            OfficialRole::RefereeAlternate => "Referee Alternate",
+        }
+    }
+
+    fn output_teams(&self, key_prefix: &String, output: &mut OutputJson) {
+        for (team_side, team) in self.teams.iter() {
+            let team_key_prefix = format!("{}.Team({})", key_prefix, team_side.number());
+            let key = |k: &str| format!("{}.{}", team_key_prefix, k);
+
+            let last_team_jam = self.periods.last().and_then(|p| p.jams.last()).map(|j| j.team_jam(*team_side));
+            let jam_score = last_team_jam.map(|t| t.jam_score()).unwrap_or(0);
+            let trip_score = last_team_jam.and_then(|t| t.trips.last()).map(|t| t.score).unwrap_or(0);
+            let score = self.get_team_score(*team_side);
+
+            output.state.insert(key("JamScore"), json!(jam_score));
+            output.state.insert(key("LastScore"), json!(score - jam_score));
+            output.state.insert(key("Score"), json!(score));
+            output.state.insert(key("TripScore"), json!(trip_score));
+
+            output.state.insert(key("AlternateName(operator)"), json!(team.name));
+            output.state.insert(key("FileName"), json!(""));
+            output.state.insert(key("FullName"), json!(team.name));
+            output.state.insert(key("Id"), json!(format!("{}_{}", self.id.as_hyphenated(), team_side.number())));
+            output.state.insert(key("Initials"), json!(team.name.split_whitespace().filter_map(|w| w.chars().next()).collect::<String>()));
+            output.state.insert(key("LeagueName"), json!(team.league));
+            output.state.insert(key("Logo"), json!(""));
+            output.state.insert(key("Name"), json!(team.name));
+            output.state.insert(key("Number"), json!(team_side.number()));
+            output.state.insert(key("PreparedTeam"), json!(team.id.as_hyphenated().to_string()));
+            output.state.insert(key("PreparedTeamConnected"), json!(false));
+            output.state.insert(key("Readonly"), json!(false));
+            output.state.insert(key("TeamName"), json!(if team.name == team.league { "".to_string() } else { team.name.clone() }));
+            output.state.insert(key("UniformColor"), json!(team.color));
+
+            if let Some((foreground, background)) = Self::get_team_color_values(&team.color) {
+                for display in ["overlay", "scoreboard"] {
+                    output.state.insert(key(&format!("Color({}_bg)", display)), json!(background));
+                    output.state.insert(key(&format!("Color({}_fg)", display)), json!(foreground));
+                }
+            }
+
+            for skater in team.roster.iter() {
+                let skater_key_prefix = format!("{}.Skater({})", team_key_prefix, skater.id.as_hyphenated());
+                let key = |k: &str| format!("{}.{}", skater_key_prefix, k);
+
+                let flags = if skater.is_captain { "C" } else if skater.is_alternate_captain { "A" } else { "" };
+                let is_in_box = self.box_trips.iter().any(|t| t.skater_id == skater.id && t.end_tick.is_none());
+
+                output.state.insert(key("BaseRole"), json!("Bench"));
+                output.state.insert(key("Flags"), json!(flags));
+                output.state.insert(key("Id"), json!(skater.id.as_hyphenated().to_string()));
+                output.state.insert(key("Name"), json!(skater.name));
+                output.state.insert(key("PenaltyBox"), json!(is_in_box));
+                output.state.insert(key("PenaltyCount"), json!(self.penalties.iter().filter(|p| p.skater_id == skater.id).count()));
+                output.state.insert(key("Pronouns"), json!(""));
+                output.state.insert(key("Readonly"), json!(false));
+                output.state.insert(key("Role"), json!("Bench"));
+                output.state.insert(key("RosterNumber"), json!(skater.number));
+            }
+        }
+    }
+
+    fn get_team_color_values(color: &str) -> Option<(&'static str, &'static str)> {
+        TEAM_COLOR_VALUES.iter()
+            .find(|(name, _, _)| name.eq_ignore_ascii_case(color))
+            .map(|(_, foreground, background)| (*foreground, *background))
+    }
+
+    fn output_timeouts(&self, key_prefix: &String, output: &mut OutputJson) {
+        for timeout in self.timeouts.iter() {
+            let timeout_key_prefix = format!("{}.Period({}).Timeout({})", key_prefix, timeout.period_number, timeout.id.as_hyphenated());
+            let key = |k: &str| format!("{}.{}", timeout_key_prefix, k);
+
+            let owner = match timeout.team {
+                Some(team) => format!("{}_{}", self.id.as_hyphenated(), team.number()),
+                None => "O".to_string(),
+            };
+            let preceding_jam_id = self.get_jam(timeout.period_number, timeout.preceding_jam_number).map(|j| j.id.as_hyphenated().to_string()).unwrap_or_default();
+
+            output.state.insert(key("Duration"), json!(timeout.end_tick.map(|t| t.saturating_sub(timeout.start_tick)).unwrap_or(0)));
+            output.state.insert(key("Id"), json!(timeout.id.as_hyphenated().to_string()));
+            output.state.insert(key("Owner"), json!(owner));
+            output.state.insert(key("PeriodClockElapsedEnd"), json!(timeout.period_clock_elapsed));
+            output.state.insert(key("PeriodClockElapsedStart"), json!(timeout.period_clock_elapsed));
+            output.state.insert(key("PeriodClockEnd"), json!(PERIOD_DURATION - timeout.period_clock_elapsed));
+            output.state.insert(key("PrecedingJam"), json!(preceding_jam_id));
+            output.state.insert(key("PrecedingJamNumber"), json!(timeout.preceding_jam_number));
+            output.state.insert(key("Readonly"), json!(false));
+            output.state.insert(key("RetainedReview"), json!(timeout.review_retained));
+            output.state.insert(key("Review"), json!(timeout.timeout_type == TimeoutType::Review));
+            output.state.insert(key("Running"), json!(false));
+            output.state.insert(key("WalltimeEnd"), json!(timeout.end_tick.unwrap_or(0)));
+            output.state.insert(key("WalltimeStart"), json!(timeout.start_tick));
+        }
+
+        for team in [TeamSide::Home, TeamSide::Away] {
+            let key = |k: &str| format!("{}.Team({}).{}", key_prefix, team.number(), k);
+
+            let team_timeouts = self.timeouts.iter().filter(|t| t.team == Some(team));
+            let timeouts_taken = team_timeouts.clone().filter(|t| t.timeout_type == TimeoutType::Team).count();
+            let current_period_reviews: Vec<&TimeoutJson> = team_timeouts
+                .filter(|t| t.timeout_type == TimeoutType::Review && t.period_number == self.periods.len())
+                .collect();
+            let review_lost = current_period_reviews.iter().any(|t| !t.review_retained);
+            let review_retained = current_period_reviews.iter().any(|t| t.review_retained);
+
+            output.state.insert(key("OfficialReviews"), json!(if review_lost { 0 } else { 1 }));
+            output.state.insert(key("RetainedOfficialReview"), json!(review_retained));
+            output.state.insert(key("Timeouts"), json!(TEAM_TIMEOUT_COUNT - timeouts_taken));
+        }
+    }
+
+    fn output_penalties(&self, key_prefix: &String, output: &mut OutputJson) {
+        for team in [TeamSide::Home, TeamSide::Away] {
+            let mut skater_ids: Vec<Uuid> = self.penalties.iter().filter(|p| p.team == team).map(|p| p.skater_id).collect();
+            skater_ids.sort();
+            skater_ids.dedup();
+
+            for skater_id in skater_ids {
+                let skater_penalties: Vec<&PenaltyJson> = self.penalties.iter().filter(|p| p.team == team && p.skater_id == skater_id).collect();
+
+                let mut penalty_number = 0;
+                for penalty in skater_penalties.iter() {
+                    penalty_number += 1;
+
+                    let penalty_key_prefix = format!("{}.Team({}).Skater({}).Penalty({})", key_prefix, team.number(), skater_id.as_hyphenated(), penalty_number);
+                    let key = |k: &str| format!("{}.{}", penalty_key_prefix, k);
+
+                    let box_trip = penalty.box_trip_id.and_then(|id| self.box_trips.iter().find(|t| t.id == id));
+                    let jam_id = self.get_jam(penalty.period_number, penalty.jam_number).map(|j| j.id.as_hyphenated().to_string()).unwrap_or_default();
+                    let next_id = skater_penalties.get(penalty_number).map(|p| p.id.as_hyphenated().to_string()).unwrap_or_default();
+                    let previous_id = if penalty_number > 1 { skater_penalties[penalty_number - 2].id.as_hyphenated().to_string() } else { "".to_string() };
+
+                    output.state.insert(key("BoxTrip"), json!(box_trip.map(|t| t.id.as_hyphenated().to_string()).unwrap_or_default()));
+                    output.state.insert(key("Code"), json!(penalty.code));
+                    output.state.insert(key("ForceServed"), json!(false));
+                    output.state.insert(key("Id"), json!(penalty.id.as_hyphenated().to_string()));
+                    output.state.insert(key("Jam"), json!(jam_id));
+                    output.state.insert(key("JamNumber"), json!(penalty.jam_number));
+                    output.state.insert(key("Next"), json!(next_id));
+                    output.state.insert(key("Number"), json!(penalty_number));
+                    output.state.insert(key("PeriodNumber"), json!(penalty.period_number));
+                    output.state.insert(key("Previous"), json!(previous_id));
+                    output.state.insert(key("Readonly"), json!(false));
+                    output.state.insert(key("Served"), json!(box_trip.is_some_and(|t| t.end_tick.is_some())));
+                    output.state.insert(key("Serving"), json!(box_trip.is_some_and(|t| t.end_tick.is_none())));
+                    output.state.insert(key("Time"), json!(penalty.tick));
+                }
+            }
+        }
+
+        for removal in self.removals.iter() {
+            let removal_key_prefix = format!("{}.Team({}).Skater({}).Penalty(0)", key_prefix, removal.team.number(), removal.skater_id.as_hyphenated());
+            let key = |k: &str| format!("{}.{}", removal_key_prefix, k);
+
+            let jam_id = self.get_jam(removal.period_number, removal.jam_number).map(|j| j.id.as_hyphenated().to_string()).unwrap_or_default();
+
+            output.state.insert(key("BoxTrip"), json!(""));
+            output.state.insert(key("Code"), json!(removal.code));
+            output.state.insert(key("ForceServed"), json!(false));
+            output.state.insert(key("Id"), json!(removal.id.as_hyphenated().to_string()));
+            output.state.insert(key("Jam"), json!(jam_id));
+            output.state.insert(key("JamNumber"), json!(removal.jam_number));
+            output.state.insert(key("Next"), json!(""));
+            output.state.insert(key("Number"), json!(0));
+            output.state.insert(key("PeriodNumber"), json!(removal.period_number));
+            output.state.insert(key("Previous"), json!(""));
+            output.state.insert(key("Readonly"), json!(false));
+            output.state.insert(key("Served"), json!(false));
+            output.state.insert(key("Serving"), json!(false));
+            output.state.insert(key("Time"), json!(removal.tick));
+        }
+    }
+
+    fn output_box_trips(&self, key_prefix: &String, output: &mut OutputJson) {
+        for box_trip in self.box_trips.iter() {
+            let box_trip_key_prefix = format!("{}.Team({}).BoxTrip({})", key_prefix, box_trip.team.number(), box_trip.id.as_hyphenated());
+            let key = |k: &str| format!("{}.{}", box_trip_key_prefix, k);
+
+            let fieldings: Vec<(usize, usize, String)> = self.periods.iter().enumerate()
+                .flat_map(|(p, period)| (0..period.jams.len()).map(move |j| (p + 1, j + 1)))
+                .filter(|(p, j)| box_trip.covers_jam(*p, *j))
+                .filter_map(|(p, j)| self.get_fielding_id(box_trip.team, p, j, box_trip.skater_id).map(|f| (p, j, f)))
+                .collect();
+
+            let penalties: Vec<&PenaltyJson> = self.penalties.iter().filter(|p| p.box_trip_id == Some(box_trip.id)).collect();
+
+            let get_jam_clock = |period_number: usize, jam_number: usize, tick: u64, between_jams: bool| {
+                match self.get_jam(period_number, jam_number) {
+                    Some(jam) if !between_jams => tick.saturating_sub(jam.start_tick),
+                    _ => 0,
+                }
+            };
+
+            let is_after_star_pass = |period_number: usize, jam_number: usize, tick: u64| {
+                self.get_jam(period_number, jam_number)
+                    .map(|j| j.team_jam(box_trip.team))
+                    .and_then(|t| t.star_pass_tick)
+                    .is_some_and(|star_pass_tick| tick > star_pass_tick)
+            };
+
+            let start_fielding = fieldings.first().map(|(_, _, f)| f.clone()).unwrap_or_default();
+            let end_fielding = if box_trip.end_tick.is_some() { fieldings.last().map(|(_, _, f)| f.clone()).unwrap_or_default() } else { "".to_string() };
+            let current_fielding = if box_trip.end_tick.is_none() { fieldings.last().map(|(_, _, f)| f.clone()).unwrap_or_default() } else { "".to_string() };
+
+            output.state.insert(key("CurrentFielding"), json!(current_fielding));
+            output.state.insert(key("Duration"), json!(box_trip.end_tick.map(|t| t.saturating_sub(box_trip.start_tick)).unwrap_or(0)));
+            output.state.insert(key("EndAfterSP"), json!(!box_trip.end_between_jams && box_trip.end_tick.is_some_and(|t| is_after_star_pass(box_trip.end_period_number, box_trip.end_jam_number, t))));
+            output.state.insert(key("EndBetweenJams"), json!(box_trip.end_between_jams));
+            output.state.insert(key("EndFielding"), json!(end_fielding));
+            output.state.insert(key("EndJamNumber"), json!(box_trip.end_jam_number));
+            output.state.insert(key("EndPeriodNumber"), json!(box_trip.end_period_number));
+            output.state.insert(key("Id"), json!(box_trip.id.as_hyphenated().to_string()));
+            output.state.insert(key("IsCurrent"), json!(box_trip.end_tick.is_none()));
+            output.state.insert(key("JamClockEnd"), json!(box_trip.end_tick.map(|t| get_jam_clock(box_trip.end_period_number, box_trip.end_jam_number, t, box_trip.end_between_jams)).unwrap_or(0)));
+            output.state.insert(key("JamClockStart"), json!(get_jam_clock(box_trip.start_period_number, box_trip.start_jam_number, box_trip.start_tick, box_trip.start_between_jams)));
+            output.state.insert(key("PenaltyCodes"), json!(penalties.iter().map(|p| p.code.as_str()).collect::<Vec<&str>>().join(" ")));
+            output.state.insert(key("Readonly"), json!(false));
+            output.state.insert(key("Skater"), json!(box_trip.skater_id.as_hyphenated().to_string()));
+            output.state.insert(key("StartAfterSP"), json!(!box_trip.start_between_jams && is_after_star_pass(box_trip.start_period_number, box_trip.start_jam_number, box_trip.start_tick)));
+            output.state.insert(key("StartBetweenJams"), json!(box_trip.start_between_jams));
+            output.state.insert(key("StartFielding"), json!(start_fielding));
+            output.state.insert(key("StartJamNumber"), json!(box_trip.start_jam_number));
+            output.state.insert(key("StartPeriodNumber"), json!(box_trip.start_period_number));
+            output.state.insert(key("TotalPenalties"), json!(penalties.len()));
+            output.state.insert(key("WalltimeEnd"), json!(box_trip.end_tick.unwrap_or(0)));
+            output.state.insert(key("WalltimeStart"), json!(box_trip.start_tick));
+
+            for (_, _, fielding) in fieldings.iter() {
+                output.state.insert(key(format!("Fielding({})", fielding).as_str()), json!(fielding));
+            }
+
+            for penalty in penalties.iter() {
+                output.state.insert(key(format!("Penalty({})", penalty.id.as_hyphenated()).as_str()), json!(penalty.id.as_hyphenated().to_string()));
+            }
+        }
+    }
+}
+
+impl Display for GameJson {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", serde_json::to_string_pretty(&self.to_output_json()).unwrap())
+    }
 }
 
 #[derive(Serialize)]
 struct OutputJson {
     state: Map<String, serde_json::Value>,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::{seeded_random_source, Game};
+
+    #[test]
+    fn head_officials_are_exported_with_head_roles() {
+        let mut game = Game::random(seeded_random_source(1));
+        game.run();
+        let json: Value = serde_json::from_str(&game.game_json.to_string()).unwrap();
+        let roles: Vec<&str> = json["state"].as_object().unwrap().iter()
+            .filter(|(key, _)| (key.contains(".Nso(") || key.contains(".Ref(")) && key.ends_with(".Role"))
+            .filter_map(|(_, role)| role.as_str())
+            .collect();
+
+        assert_eq!(roles.iter().filter(|r| **r == "Head Referee").count(), 1);
+        assert_eq!(roles.iter().filter(|r| **r == "Head Non-Skating Official").count(), 1);
+        assert_eq!(roles.len(), game.officials.len());
+    }
+}
@@ -0,0 +1,649 @@
+use std::{collections::BTreeMap, path::Path};
+
+use chrono::NaiveDate;
+use rand::{SeedableRng, rngs::StdRng};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{game_events::TimeoutType, official::{Official, OfficialRole}, penalty_code, random_id, skater::{Position, Skater}, team::Team, team_pair::TeamPair, team_side::TeamSide};
+
+use super::{BoxTripJson, FieldingSkaterJson, GameJson, JamJson, PenaltyJson, PeriodJson, TeamJamFielding, TeamJamJson, TimeoutJson, TripJson, TEAM_COLOR_VALUES};
+
+/// Skating speed given to imported skaters, the middle of the range given to random skaters.
+const IMPORTED_SKATER_SPEED: f32 = 17.5;
+/// Penalty chance given to imported skaters, the middle of the range given to random skaters.
+const IMPORTED_SKATER_PENALTY_CHANCE: f64 = 1.0 / 1500.0;
+
+/// One segment of a CRG state key, e.g. `Jam(3)`, with the children of every key sharing its prefix.
+#[derive(Default)]
+struct StateNode<'a> {
+    value: Option<&'a Value>,
+    children: BTreeMap<&'a str, StateNode<'a>>,
+}
+
+impl<'a> StateNode<'a> {
+    fn insert(&mut self, segments: &[&'a str], value: &'a Value) {
+        match segments.split_first() {
+            Some((first, rest)) => self.children.entry(first).or_default().insert(rest, value),
+            None => self.value = Some(value),
+        }
+    }
+
+    fn child(&self, name: &str) -> Option<&StateNode<'a>> {
+        self.children.get(name)
+    }
+
+    /// The children named `name(id)`, with their ids.
+    fn children_named<'s>(&'s self, name: &str) -> impl Iterator<Item = (&'a str, &'s StateNode<'a>)> + 's {
+        let prefix = format!("{}(", name);
+
+        self.children.iter().filter_map(move |(key, node)| {
+            let key: &'a str = key;
+            key.strip_prefix(prefix.as_str()).and_then(|k| k.strip_suffix(')')).map(|id| (id, node))
+        })
+    }
+
+    /// The children named `name(number)` with a number of one or more, in number order.
+    fn numbered_children<'s>(&'s self, name: &str) -> Vec<(usize, &'s StateNode<'a>)> {
+        let mut children: Vec<_> = self.children_named(name)
+            .filter_map(|(id, node)| id.parse::<usize>().ok().filter(|n| *n > 0).map(|n| (n, node)))
+            .collect();
+        children.sort_by_key(|(n, _)| *n);
+        children
+    }
+
+    fn get(&self, key: &str) -> Option<&'a Value> {
+        self.child(key).and_then(|c| c.value)
+    }
+
+    fn str(&self, key: &str) -> &'a str {
+        self.get(key).and_then(|v| v.as_str()).unwrap_or_default()
+    }
+
+    fn bool(&self, key: &str) -> bool {
+        self.get(key).and_then(|v| v.as_bool()).unwrap_or_default()
+    }
+
+    fn u64(&self, key: &str) -> Option<u64> {
+        self.get(key).and_then(|v| v.as_u64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+    }
+
+    fn uuid(&self, key: &str) -> Option<Uuid> {
+        Uuid::parse_str(self.str(key)).ok()
+    }
+}
+
+/// Splits a state key on the dots between segments, ignoring dots within brackets, e.g. `Rule(Jam.Duration)`.
+fn split_key(key: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, character) in key.char_indices() {
+        match character {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '.' if depth == 0 => {
+                segments.push(&key[start..index]);
+                start = index + 1;
+            },
+            _ => {},
+        }
+    }
+    segments.push(&key[start..]);
+
+    segments
+}
+
+fn team_side(number: usize) -> Option<TeamSide> {
+    match number {
+        1 => Some(TeamSide::Home),
+        2 => Some(TeamSide::Away),
+        _ => None,
+    }
+}
+
+/// The team referenced by a CRG team id, which ends with the team number, e.g. `<game id>_1`.
+fn team_side_from_id(id: &str) -> Option<TeamSide> {
+    id.rsplit('_').next().and_then(|n| n.parse().ok()).and_then(team_side)
+}
+
+impl GameJson {
+    /// Loads a game from a CRG scoreboard JSON file, see [`GameJson::from_crg_json`].
+    pub fn from_crg_file(path: &Path) -> Result<GameJson, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+        Self::from_crg_json(&contents)
+    }
+
+    /// Reads the first `ScoreBoard.Game(...)` from CRG scoreboard JSON, as written by [`GameJson`] or exported by CRG.
+    ///
+    /// Values CRG does not record, such as when each trip was completed or skaters' speeds, are approximated from what
+    /// it does record.
+    pub fn from_crg_json(contents: &str) -> Result<GameJson, String> {
+        let json: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+        let state = json.get("state").and_then(|s| s.as_object()).ok_or("Expected a CRG state file with a 'state' object")?;
+
+        let mut root = StateNode::default();
+        for (key, value) in state.iter() {
+            root.insert(&split_key(key), value);
+        }
+
+        let (game_id, game) = root.child("ScoreBoard")
+            .and_then(|s| s.children_named("Game").next())
+            .ok_or("No ScoreBoard.Game found")?;
+        let id = Uuid::parse_str(game_id).map_err(|e| format!("Invalid game id '{}': {}", game_id, e))?;
+
+        let mut id_source = StdRng::seed_from_u64(id.as_u64_pair().0);
+        let mut new_id = |id: Option<Uuid>| id.unwrap_or_else(|| random_id::random_uuid(&mut id_source));
+
+        let clock_ids = ["Intermission", "Jam", "Lineup", "Period", "Timeout"]
+            .map(|name| new_id(game.child(&format!("Clock({})", name)).and_then(|c| c.uuid("Id"))));
+
+        let periods = Self::import_periods(game, &mut new_id);
+        let first_jam_id = new_id(Self::first_jam(game).and_then(|j| j.uuid("Previous")));
+        let upcoming_jam_id = new_id(Self::last_jam(game).and_then(|j| j.uuid("Next")));
+
+        let teams: Vec<(TeamSide, Team)> = game.numbered_children("Team").into_iter()
+            .filter_map(|(number, team)| team_side(number).map(|side| (side, Self::import_team(game, side, team, &mut new_id))))
+            .collect();
+
+        let penalties = Self::import_penalties(game, false);
+        let removals = Self::import_penalties(game, true);
+        let box_trips = Self::import_box_trips(game);
+        let timeouts = Self::import_timeouts(game, &mut new_id);
+        let mut import_warnings = Vec::new();
+        let officials_crew = Self::import_officials(game, &mut import_warnings);
+
+        let mut game_json = GameJson {
+            id,
+            id_source,
+            clock_ids,
+            first_jam_id,
+            upcoming_jam_id,
+            event_date: NaiveDate::parse_from_str(game.str("EventInfo(Date)"), "%Y-%m-%d").unwrap_or_default(),
+            teams,
+            officials_crew,
+            periods,
+            penalties,
+            removals,
+            box_trips,
+            timeouts,
+            import_warnings,
+        };
+
+        game_json.link_penalty_substitutes();
+        game_json.narrow_star_passes(game);
+
+        Ok(game_json)
+    }
+
+    fn first_jam<'a>(game: &'a StateNode) -> Option<&'a StateNode<'a>> {
+        game.numbered_children("Period").first().and_then(|(_, p)| p.numbered_children("Jam").first().map(|(_, j)| *j))
+    }
+
+    fn last_jam<'a>(game: &'a StateNode) -> Option<&'a StateNode<'a>> {
+        game.numbered_children("Period").last().and_then(|(_, p)| p.numbered_children("Jam").last().map(|(_, j)| *j))
+    }
+
+    fn import_periods(game: &StateNode, new_id: &mut impl FnMut(Option<Uuid>) -> Uuid) -> Vec<PeriodJson> {
+        game.numbered_children("Period").into_iter().map(|(_, period)| {
+            let jams: Vec<JamJson> = period.numbered_children("Jam").into_iter()
+                .map(|(_, jam)| Self::import_jam(period, jam, new_id))
+                .collect();
+            let start_tick = period.u64("WalltimeStart").or_else(|| jams.first().map(|j| j.start_tick)).unwrap_or(0);
+
+            PeriodJson {
+                id: new_id(period.uuid("Id").or_else(|| period.uuid("id"))),
+                start_tick,
+                duration: period.u64("Duration").unwrap_or(0),
+                jams,
+            }
+        }).collect()
+    }
+
+    fn import_jam(period: &StateNode, jam: &StateNode, new_id: &mut impl FnMut(Option<Uuid>) -> Uuid) -> JamJson {
+        let period_clock_elapsed_start = jam.u64("PeriodClockElapsedStart").unwrap_or(0);
+        let start_tick = jam.u64("WalltimeStart").unwrap_or(period.u64("WalltimeStart").unwrap_or(0) + period_clock_elapsed_start);
+        let end_tick = jam.u64("WalltimeEnd").filter(|t| *t != 0).unwrap_or(start_tick + jam.u64("Duration").unwrap_or(0));
+
+        let team_jams = TeamPair::new((), ()).map(|team, _| {
+            jam.child(&format!("TeamJam({})", team.number()))
+                .map(|team_jam| Self::import_team_jam(team_jam, start_tick, new_id))
+                .unwrap_or_else(Self::empty_team_jam)
+        });
+
+        JamJson {
+            id: new_id(jam.uuid("Id")),
+            start_tick,
+            end_tick,
+            period_clock_elapsed_start,
+            period_clock_elapsed_end: jam.u64("PeriodClockElapsedEnd").unwrap_or(0),
+            is_overtime: jam.bool("Overtime"),
+            team_jams,
+        }
+    }
+
+    fn empty_team_jam() -> TeamJamJson {
+        TeamJamJson {
+            fielding: TeamJamFielding { jammer: None, pivot: None, blocker1: None, blocker2: None, blocker3: None },
+            called_off: false,
+            is_lead: false,
+            lost_lead: false,
+            injury: false,
+            injured_skater_id: None,
+            star_pass_tick: None,
+            trips: Vec::new(),
+        }
+    }
+
+    fn import_team_jam(team_jam: &StateNode, jam_start_tick: u64, new_id: &mut impl FnMut(Option<Uuid>) -> Uuid) -> TeamJamJson {
+        let fielding_skater = |position: &str| -> Option<FieldingSkaterJson> {
+            let fielding = team_jam.child(&format!("Fielding({})", position))?;
+            let skater_id = fielding.uuid("Skater").filter(|_| !fielding.bool("NotFielded"))?;
+
+            Some(FieldingSkaterJson { skater_id, number: fielding.str("SkaterNumber").to_string() })
+        };
+
+        let injured_skater_id = ["Jammer", "Pivot", "Blocker1", "Blocker2", "Blocker3"].into_iter()
+            .filter_map(|p| team_jam.child(&format!("Fielding({})", p)))
+            .find(|f| f.bool("SitFor3"))
+            .and_then(|f| f.uuid("Skater"));
+
+        // CRG does not record whether a trip was completed, so every trip but the last is treated as complete, with the
+        // initial trip following the no initial flag.
+        let scoring_trips = team_jam.numbered_children("ScoringTrip");
+        let trip_count = scoring_trips.len();
+        let has_no_initial = team_jam.bool("NoInitial");
+        let trips: Vec<TripJson> = scoring_trips.into_iter().map(|(number, trip)| TripJson {
+            id: new_id(trip.uuid("Id")),
+            after_star_pass: trip.bool("AfterSP"),
+            start_tick: jam_start_tick + trip.u64("JamClockStart").unwrap_or(0),
+            duration: trip.u64("Duration").unwrap_or(0),
+            score: trip.u64("Score").unwrap_or(0) as u8,
+            is_complete: if number == 1 { !has_no_initial } else { number < trip_count },
+        }).collect();
+
+        let star_pass_tick = if team_jam.bool("StarPass") {
+            Some(trips.iter().find(|t| t.after_star_pass).map(|t| t.start_tick).unwrap_or(jam_start_tick))
+        } else {
+            None
+        };
+
+        TeamJamJson {
+            fielding: TeamJamFielding {
+                jammer: fielding_skater("Jammer"),
+                pivot: fielding_skater("Pivot"),
+                blocker1: fielding_skater("Blocker1"),
+                blocker2: fielding_skater("Blocker2"),
+                blocker3: fielding_skater("Blocker3"),
+            },
+            called_off: team_jam.bool("Calloff"),
+            is_lead: team_jam.bool("Lead"),
+            lost_lead: team_jam.bool("Lost"),
+            injury: team_jam.bool("Injury"),
+            injured_skater_id,
+            star_pass_tick,
+            trips,
+        }
+    }
+
+    fn import_team(game: &StateNode, side: TeamSide, team: &StateNode, new_id: &mut impl FnMut(Option<Uuid>) -> Uuid) -> Team {
+        let name = [team.str("Name"), team.str("FullName"), team.str("LeagueName")].into_iter()
+            .find(|n| !n.is_empty())
+            .unwrap_or_default()
+            .to_string();
+        let league = match team.str("LeagueName") {
+            "" => name.clone(),
+            league => league.to_string(),
+        };
+
+        let mut roster: Vec<Skater> = team.children_named("Skater")
+            .filter(|(_, skater)| !skater.str("Flags").starts_with('B'))
+            .filter_map(|(id, skater)| {
+                let id = Uuid::parse_str(id).ok()?;
+                let flags = skater.str("Flags");
+
+                Some(Skater {
+                    id,
+                    name: skater.str("Name").to_string(),
+                    number: skater.str("RosterNumber").to_string(),
+                    favored_position: Self::most_fielded_position(game, side, id),
+                    base_speed: IMPORTED_SKATER_SPEED,
+                    penalty_chance: IMPORTED_SKATER_PENALTY_CHANCE,
+                    is_captain: flags == "C",
+                    is_alternate_captain: flags == "A",
+                })
+            })
+            .collect();
+        roster.sort_by(|a, b| a.number.cmp(&b.number));
+
+        Team {
+            id: new_id(team.uuid("PreparedTeam")),
+            name,
+            league,
+            roster,
+            color: Self::team_color(team),
+        }
+    }
+
+    /// The uniform color, or the name of the scoreboard color if the uniform color wasn't entered.
+    fn team_color(team: &StateNode) -> String {
+        match team.str("UniformColor") {
+            "" => {
+                let background = team.str("Color(scoreboard_bg)");
+
+                TEAM_COLOR_VALUES.iter()
+                    .find(|(_, _, b)| b.eq_ignore_ascii_case(background))
+                    .map(|(name, _, _)| name.to_string())
+                    .unwrap_or_else(|| background.to_string())
+            },
+            color => color.to_string(),
+        }
+    }
+
+    fn most_fielded_position(game: &StateNode, side: TeamSide, skater_id: Uuid) -> Position {
+        let mut counts = [(Position::Blocker, 0), (Position::Pivot, 0), (Position::Jammer, 0)];
+
+        let team_jams = game.numbered_children("Period").into_iter()
+            .flat_map(|(_, p)| p.numbered_children("Jam"))
+            .filter_map(|(_, j)| j.child(&format!("TeamJam({})", side.number())));
+        for team_jam in team_jams {
+            for (fielding_name, fielding) in team_jam.children_named("Fielding") {
+                if fielding.uuid("Skater") != Some(skater_id) {
+                    continue;
+                }
+
+                let position_index = match fielding_name {
+                    "Jammer" => 2,
+                    "Pivot" => 1,
+                    _ => 0,
+                };
+                counts[position_index].1 += 1;
+            }
+        }
+
+        counts.into_iter().max_by_key(|(_, count)| *count).map(|(position, _)| position).unwrap_or(Position::Blocker)
+    }
+
+    /// Imports penalties, or the foul-outs and expulsions CRG records as each skater's `Penalty(0)`.
+    fn import_penalties(game: &StateNode, removals: bool) -> Vec<PenaltyJson> {
+        let mut penalties: Vec<PenaltyJson> = game.numbered_children("Team").into_iter()
+            .filter_map(|(number, team)| team_side(number).map(|side| (side, team)))
+            .flat_map(|(side, team)| team.children_named("Skater").flat_map(move |(skater_id, skater)| {
+                skater.children_named("Penalty").filter_map(move |(number, penalty)| {
+                    let number: usize = number.parse().ok()?;
+                    if (number == 0) != removals {
+                        return None;
+                    }
+
+                    let code = match penalty.str("Code") {
+                        code if !removals => code.to_string(),
+                        penalty_code::FOULED_OUT => penalty_code::FOULED_OUT.to_string(),
+                        _ => penalty_code::EXPELLED.to_string(),
+                    };
+
+                    Some(PenaltyJson {
+                        id: penalty.uuid("Id").unwrap_or_else(Uuid::nil),
+                        team: side,
+                        skater_id: Uuid::parse_str(skater_id).ok()?,
+                        code,
+                        period_number: penalty.u64("PeriodNumber").unwrap_or(0) as usize,
+                        jam_number: penalty.u64("JamNumber").unwrap_or(0) as usize,
+                        tick: penalty.u64("Time").unwrap_or(0),
+                        box_trip_id: penalty.uuid("BoxTrip"),
+                        substitute_id: None,
+                    })
+                })
+            }))
+            .collect();
+
+        penalties.sort_by_key(|p| (p.period_number, p.jam_number, p.tick));
+        penalties
+    }
+
+    fn import_box_trips(game: &StateNode) -> Vec<BoxTripJson> {
+        let mut box_trips: Vec<BoxTripJson> = game.numbered_children("Team").into_iter()
+            .filter_map(|(number, team)| team_side(number).map(|side| (side, team)))
+            .flat_map(|(side, team)| team.children_named("BoxTrip").filter_map(move |(id, box_trip)| {
+                let is_current = box_trip.bool("IsCurrent");
+
+                Some(BoxTripJson {
+                    id: Uuid::parse_str(id).ok()?,
+                    team: side,
+                    skater_id: box_trip.uuid("Skater")?,
+                    start_period_number: box_trip.u64("StartPeriodNumber").unwrap_or(0) as usize,
+                    start_jam_number: box_trip.u64("StartJamNumber").unwrap_or(0) as usize,
+                    start_between_jams: box_trip.bool("StartBetweenJams"),
+                    start_tick: box_trip.u64("WalltimeStart").unwrap_or(0),
+                    end_period_number: if is_current { 0 } else { box_trip.u64("EndPeriodNumber").unwrap_or(0) as usize },
+                    end_jam_number: if is_current { 0 } else { box_trip.u64("EndJamNumber").unwrap_or(0) as usize },
+                    end_between_jams: !is_current && box_trip.bool("EndBetweenJams"),
+                    end_tick: if is_current { None } else { box_trip.u64("WalltimeEnd") },
+                })
+            }))
+            .collect();
+
+        box_trips.sort_by_key(|t| (t.start_period_number, t.start_jam_number, t.start_tick));
+        box_trips
+    }
+
+    fn import_timeouts(game: &StateNode, new_id: &mut impl FnMut(Option<Uuid>) -> Uuid) -> Vec<TimeoutJson> {
+        let mut timeouts: Vec<TimeoutJson> = game.numbered_children("Period").into_iter()
+            .flat_map(|(period_number, period)| period.children_named("Timeout").map(move |(id, timeout)| (period_number, id, timeout)))
+            .map(|(period_number, id, timeout)| {
+                let team = team_side_from_id(timeout.str("Owner"));
+                let timeout_type = match team {
+                    Some(_) if timeout.bool("Review") => TimeoutType::Review,
+                    Some(_) => TimeoutType::Team,
+                    None => TimeoutType::Official,
+                };
+
+                TimeoutJson {
+                    id: new_id(Uuid::parse_str(id).ok()),
+                    timeout_type,
+                    team,
+                    review_retained: timeout.bool("RetainedReview"),
+                    period_number,
+                    preceding_jam_number: timeout.u64("PrecedingJamNumber").unwrap_or(0) as usize,
+                    start_tick: timeout.u64("WalltimeStart").unwrap_or(0),
+                    end_tick: timeout.u64("WalltimeEnd").filter(|_| !timeout.bool("Running")),
+                    period_clock_elapsed: timeout.u64("PeriodClockElapsedStart").unwrap_or(0),
+                }
+            })
+            .collect();
+
+        timeouts.sort_by_key(|t| (t.period_number, t.start_tick));
+        timeouts
+    }
+
+    /// CRG only records that head officials are the head, so they're imported as an inside pack referee or penalty
+    /// lineup tracker. Officials with any other role that isn't recognised are left out of the crew with a warning.
+    fn import_officials(game: &StateNode, warnings: &mut Vec<String>) -> Vec<Official> {
+        let head_nso = game.str("HNSO");
+        let head_referee = game.str("HR");
+
+        ["Nso", "Ref"].into_iter()
+            .flat_map(|official_type| game.children_named(official_type).map(move |o| (official_type, o)))
+            .filter_map(|(official_type, (id, official))| {
+                let name = official.str("Name").to_string();
+                let role_name = official.str("Role");
+                let is_head_role = role_name.to_lowercase().starts_with("head");
+                let head_role = if official_type == "Ref" { OfficialRole::InsidePackReferee } else { OfficialRole::PenaltyLineupTracker };
+
+                let role = match role_name.parse::<OfficialRole>() {
+                    Ok(role) => role,
+                    Err(_) if is_head_role => head_role,
+                    Err(e) => {
+                        warnings.push(format!("{} for official '{}', leaving them out of the crew", e, name));
+                        return None;
+                    },
+                };
+                let head_name = if official_type == "Ref" { head_referee } else { head_nso };
+
+                Some(Official {
+                    id: Uuid::parse_str(id).ok()?,
+                    is_head: is_head_role || (!head_name.is_empty() && name == head_name),
+                    name,
+                    role,
+                    league: Some(official.str("League").to_string()).filter(|l| !l.is_empty()),
+                    certification: Some(official.str("Cert").to_string()).filter(|c| !c.is_empty()),
+                    team: team_side_from_id(official.str("P1Team")),
+                })
+            })
+            .collect()
+    }
+
+    /// CRG only records which scoring trip a star pass happened on, so the star pass is first placed at the start of
+    /// that trip, then moved to just after every box trip start or end in the jam that CRG marks as before the star pass.
+    fn narrow_star_passes(&mut self, game: &StateNode) {
+        let mut bounds: Vec<(TeamSide, usize, usize, u64, bool)> = Vec::new();
+
+        for box_trip in self.box_trips.iter() {
+            let Some(crg_box_trip) = game.child(&format!("Team({})", box_trip.team.number()))
+                .and_then(|t| t.child(&format!("BoxTrip({})", box_trip.id.as_hyphenated())))
+            else {
+                continue;
+            };
+
+            if !box_trip.start_between_jams {
+                bounds.push((box_trip.team, box_trip.start_period_number, box_trip.start_jam_number, box_trip.start_tick, crg_box_trip.bool("StartAfterSP")));
+            }
+
+            if let Some(end_tick) = box_trip.end_tick {
+                bounds.push((box_trip.team, box_trip.end_period_number, box_trip.end_jam_number, end_tick, crg_box_trip.bool("EndAfterSP")));
+            }
+        }
+
+        for (period_number, period) in self.periods.iter_mut().enumerate().map(|(i, p)| (i + 1, p)) {
+            for (jam_number, jam) in period.jams.iter_mut().enumerate().map(|(i, j)| (i + 1, j)) {
+                let jam_end_tick = jam.end_tick;
+
+                for (team, team_jam) in jam.team_jams.iter_mut() {
+                    let Some(star_pass_tick) = team_jam.star_pass_tick else {
+                        continue;
+                    };
+
+                    let (earliest, latest) = bounds.iter()
+                        .filter(|(t, p, j, _, _)| *t == team && *p == period_number && *j == jam_number)
+                        .fold((star_pass_tick, jam_end_tick), |(earliest, latest), (_, _, _, tick, is_after_star_pass)| {
+                            if *is_after_star_pass {
+                                (earliest, latest.min(tick.saturating_sub(1)))
+                            } else {
+                                (earliest.max(*tick), latest)
+                            }
+                        });
+
+                    if earliest <= latest {
+                        team_jam.star_pass_tick = Some(earliest);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Penalties served in the box by a different skater to the one penalized were served by a substitute.
+    fn link_penalty_substitutes(&mut self) {
+        for penalty in self.penalties.iter_mut() {
+            let box_trip_skater_id = penalty.box_trip_id.and_then(|id| self.box_trips.iter().find(|t| t.id == id)).map(|t| t.skater_id);
+
+            penalty.substitute_id = box_trip_skater_id.filter(|id| *id != penalty.skater_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{official::OfficialRole, seeded_random_source, Game, GameJson};
+
+    fn simulated_game(seed: u64) -> GameJson {
+        let mut game = Game::random(seeded_random_source(seed));
+        game.run();
+        game.game_json
+    }
+
+    fn state(game_json: &GameJson) -> serde_json::Map<String, Value> {
+        let json: Value = serde_json::from_str(&game_json.to_string()).unwrap();
+        json["state"].as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn reexported_game_matches_original() {
+        for seed in [1, 4, 6, 7, 8, 9, 10, 15, 28] {
+            let original = state(&simulated_game(seed));
+            let imported = GameJson::from_crg_json(&simulated_game(seed).to_string()).unwrap();
+            let reexported = state(&imported);
+
+            let differences: Vec<String> = original.keys().chain(reexported.keys().filter(|k| !original.contains_key(*k)))
+                .filter(|key| original.get(*key) != reexported.get(*key))
+                .map(|key| format!("{}: {:?} != {:?}", key, original.get(key), reexported.get(key)))
+                .collect();
+
+            assert!(differences.is_empty(), "Seed {} changed on re-export: {:#?}", seed, differences);
+        }
+    }
+
+    #[test]
+    fn games_with_a_jam_in_progress_can_be_reexported() {
+        let game_json = simulated_game(1);
+        let last_period = game_json.periods().len();
+        let last_jam = game_json.periods().last().unwrap().jam_count();
+        let jam_end_key = format!(".Period({}).Jam({}).WalltimeEnd", last_period, last_jam);
+
+        let original = state(&game_json);
+        let mut in_progress = original.clone();
+        let key = in_progress.keys().find(|k| k.ends_with(&jam_end_key)).unwrap().clone();
+        in_progress.insert(key, json!(0));
+
+        let imported = GameJson::from_crg_json(&json!({ "state": in_progress }).to_string()).unwrap();
+
+        assert_eq!(state(&imported), original);
+    }
+
+    #[test]
+    fn games_without_periods_jams_or_trips_can_be_reexported() {
+        let game = "ScoreBoard.Game(9b7b5b5e-3c1c-4c8e-9a57-2f7f2b1f4a10)";
+        let states = [
+            json!({ format!("{}.Id", game): "9b7b5b5e-3c1c-4c8e-9a57-2f7f2b1f4a10" }),
+            json!({ format!("{}.Period(1).Duration", game): 0 }),
+            json!({
+                format!("{}.Period(1).Jam(1).Duration", game): 1000,
+                format!("{}.Period(2).Duration", game): 0,
+                format!("{}.Period(3).Jam(1).TeamJam(1).Lead", game): true,
+            }),
+        ];
+
+        for state in states {
+            let game_json = GameJson::from_crg_json(&json!({ "state": state }).to_string()).unwrap();
+
+            assert!(!game_json.to_string().is_empty());
+        }
+    }
+
+    #[test]
+    fn officials_with_unknown_roles_are_left_out_with_a_warning() {
+        let game = "ScoreBoard.Game(9b7b5b5e-3c1c-4c8e-9a57-2f7f2b1f4a10)";
+        let state = json!({
+            format!("{}.Nso(0c0e3a5e-8d56-4a0b-b7a4-1d3f1c8e0a01).Name", game): "Known NSO",
+            format!("{}.Nso(0c0e3a5e-8d56-4a0b-b7a4-1d3f1c8e0a01).Role", game): "Jam Timer",
+            format!("{}.Nso(0c0e3a5e-8d56-4a0b-b7a4-1d3f1c8e0a02).Name", game): "Alternate NSO",
+            format!("{}.Nso(0c0e3a5e-8d56-4a0b-b7a4-1d3f1c8e0a02).Role", game): "Non-Skating Official Alternate",
+            format!("{}.Ref(0c0e3a5e-8d56-4a0b-b7a4-1d3f1c8e0a03).Name", game): "Alternate Ref",
+            format!("{}.Ref(0c0e3a5e-8d56-4a0b-b7a4-1d3f1c8e0a03).Role", game): "Referee Alternate",
+            format!("{}.Nso(0c0e3a5e-8d56-4a0b-b7a4-1d3f1c8e0a04).Name", game): "Announcer",
+            format!("{}.Nso(0c0e3a5e-8d56-4a0b-b7a4-1d3f1c8e0a04).Role", game): "Announcer",
+        });
+
+        let game_json = GameJson::from_crg_json(&json!({ "state": state }).to_string()).unwrap();
+        let role = |name: &str| game_json.officials().iter().find(|o| o.name == name).map(|o| o.role);
+
+        assert_eq!(game_json.officials().len(), 3);
+        assert_eq!(role("Known NSO"), Some(OfficialRole::JamTimer));
+        assert_eq!(role("Alternate NSO"), Some(OfficialRole::NonSkatingOfficialAlternate));
+        assert_eq!(role("Alternate Ref"), Some(OfficialRole::RefereeAlternate));
+        assert_eq!(role("Announcer"), None);
+        assert_eq!(game_json.import_warnings().len(), 1);
+    }
+}
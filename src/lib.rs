@@ -0,0 +1,53 @@
+//! Simulates roller derby games and exports them in the formats used by scoreboard and officiating tools.
+//!
+//! A game is generated with [`Game::random`], simulated to completion with [`Game::run`], and can then be inspected
+//! directly or exported through [`GameJson`] (CRG scoreboard JSON), [`GameEvents`] (chronological event log) and
+//! [`Statsbook`] (a statsbook-like CSV export of the WFTDA statsbook tabs), and rendered as printable NSO [`Paperwork`]. For NSO training,
+//! [`Game::record_with_errors`] produces a [`RecordedGame`] copy with injected official mistakes. Finished and imported
+//! games can be checked against the rules with [`Validation`].
+
+pub mod crew_file;
+pub mod game;
+pub mod game_events;
+pub mod game_json;
+pub mod official;
+pub mod paperwork;
+pub mod penalty_code;
+pub mod recording_errors;
+pub mod roster_file;
+pub mod skater;
+pub mod statsbook;
+pub mod team;
+pub mod team_pair;
+pub mod team_side;
+pub mod validation;
+
+mod clock;
+mod pack;
+mod random_id;
+mod rules;
+mod word_list;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+pub use game::Game;
+pub use game_events::GameEvents;
+pub use game_json::GameJson;
+pub use official::Official;
+pub use paperwork::Paperwork;
+pub use penalty_code::PenaltyCodeWeights;
+pub use recording_errors::RecordedGame;
+pub use skater::Skater;
+pub use statsbook::Statsbook;
+pub use team::Team;
+pub use team_pair::TeamPair;
+pub use validation::Validation;
+
+/// Creates a random source from a numeric seed, matching the `--seed` option of the command line tool.
+pub fn seeded_random_source(seed: u64) -> StdRng {
+    let seed_bytes = u64::to_le_bytes(seed);
+    let mut seed_buffer = [0; 32];
+    seed_buffer[..seed_bytes.len()].copy_from_slice(&seed_bytes);
+
+    StdRng::from_seed(seed_buffer)
+}
@@ -1,18 +1,9 @@
-mod game;
-mod game_json;
-mod official;
-mod skater;
-mod team;
-mod word_list;
-
-use std::path::PathBuf;
-
-use crate::skater::Skater;
-use clap::Parser;
-use game::Game;
-use official::Official;
+use std::{path::{Path, PathBuf}, process::exit};
+
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use derby_game_gen::{team_side::TeamSide, Game, GameJson, Official, Paperwork, PenaltyCodeWeights, RecordedGame, Skater, Statsbook, Team, TeamPair, Validation};
 use rand::{rngs::StdRng, SeedableRng};
-use team::Team;
 
 #[derive(Parser, Debug)]
 struct CommandLineArguments {
@@ -27,6 +18,64 @@ struct CommandLineArguments {
     /// The file path to output the events YAML to
     #[arg(short = 'y', long = "eventsYaml")]
     yaml_output_path: Option<PathBuf>,
+
+    /// The directory to output a statsbook-like CSV export to, one sheet per statsbook tab. This is not the WFTDA
+    /// statsbook's exact cell layout or xlsx file
+    #[arg(short = 'b', long = "statsbook")]
+    statsbook_output_path: Option<PathBuf>,
+
+    /// The directory to output the printable NSO paperwork HTML sheets to
+    #[arg(long = "paperwork")]
+    paperwork_output_path: Option<PathBuf>,
+
+    /// The directory to output an as-recorded copy of the game with injected official errors to, along with a
+    /// report of the errors
+    #[arg(long = "recorded")]
+    recorded_output_path: Option<PathBuf>,
+
+    /// The chance of each trip score, lead, penalty and box trip being recorded wrongly in the as-recorded copy
+    #[arg(long = "errorRate", default_value_t = 0.05)]
+    error_rate: f64,
+
+    /// Relative penalty code frequencies as CODE=WEIGHT pairs, e.g. "B=20,X=13,G=1"
+    #[arg(short = 'p', long = "penaltyWeights")]
+    penalty_code_weights: Option<PenaltyCodeWeights>,
+
+    /// The event date written to the game JSON (YYYY-MM-DD), randomly generated if not given
+    #[arg(short = 'd', long = "date")]
+    event_date: Option<NaiveDate>,
+
+    /// A JSON or CSV roster file for the home team, randomly generated if not given
+    #[arg(long = "homeRoster")]
+    home_roster_path: Option<PathBuf>,
+
+    /// A JSON or CSV roster file for the away team, randomly generated if not given
+    #[arg(long = "awayRoster")]
+    away_roster_path: Option<PathBuf>,
+
+    /// A JSON or CSV officials crew file, randomly generated if not given
+    #[arg(long = "officials")]
+    officials_path: Option<PathBuf>,
+
+    /// Print the progress of the simulation as it runs
+    #[arg(short = 'v', long = "verbose")]
+    verbose: bool,
+
+    /// A CRG game JSON file whose teams, officials and event date are used for the simulated game
+    #[arg(long = "fromGame", conflicts_with_all = ["home_roster_path", "away_roster_path", "officials_path"])]
+    seed_game_path: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Checks a game for rule and consistency violations, exiting with an error if any are found
+    Validate {
+        /// A CRG game JSON file to check instead of simulating a game
+        game_path: Option<PathBuf>,
+    },
 }
 
 fn print_skater(skater: &Skater) {
@@ -36,56 +85,196 @@ fn print_skater(skater: &Skater) {
 fn print_team(team: &Team) {
     println!("{} - {}", team.name, team.color);
     for skater in team.roster.iter() {
-        print_skater(&skater);
+        print_skater(skater);
     }
 }
 
 fn print_official(official: &Official) {
+    let team = match official.team {
+        Some(TeamSide::Home) => " for Home",
+        Some(TeamSide::Away) => " for Away",
+        None => "",
+    };
+
     if official.is_head {
-        println!("{} - {:?} (Head)", official.name, official.role);
+        println!("{} - {:?}{} (Head)", official.name, official.role, team);
+    } else {
+        println!("{} - {:?}{}", official.name, official.role, team);
+    }
+}
+
+fn print_result(game_json: &GameJson, home_team: &Team, away_team: &Team) {
+    println!("{} {} - {} {}", home_team.name, game_json.get_team_score(TeamSide::Home), game_json.get_team_score(TeamSide::Away), away_team.name);
+
+    let mut period_number = 0;
+    for period in game_json.periods() {
+        period_number += 1;
+        println!("Period {}: {} - {}", period_number, period.team_score(TeamSide::Home), period.team_score(TeamSide::Away));
+    }
+
+    let overtime_jams: Vec<_> = game_json.periods().iter().flat_map(|p| p.jams()).filter(|j| j.is_overtime).collect();
+    if !overtime_jams.is_empty() {
+        let overtime_score = |team| overtime_jams.iter().map(|j| j.team_jam(team).jam_score()).sum::<u32>();
+        println!("Overtime ({} jams): {} - {}", overtime_jams.len(), overtime_score(TeamSide::Home), overtime_score(TeamSide::Away));
+    }
+}
+
+fn get_team(roster_path: &Option<PathBuf>, random_source: &mut StdRng) -> Team {
+    match roster_path {
+        Some(path) => match Team::from_roster_file(path, random_source) {
+            Ok(team) => team,
+            Err(e) => {
+                println!("Error loading roster {}: {}", path.to_str().unwrap(), e);
+                exit(1);
+            }
+        },
+        None => Team::random(random_source),
+    }
+}
+
+fn get_officials(officials_path: &Option<PathBuf>, random_source: &mut StdRng) -> Vec<Official> {
+    match officials_path {
+        Some(path) => match Official::crew_from_file(path, random_source) {
+            Ok(officials) => officials,
+            Err(e) => {
+                println!("Error loading officials {}: {}", path.to_str().unwrap(), e);
+                exit(1);
+            }
+        },
+        None => Official::random_crew(random_source),
+    }
+}
+
+/// Loads a CRG game file, printing any import warnings and exiting if it can't be loaded.
+fn load_game(game_path: &Path) -> GameJson {
+    let game_json = match GameJson::from_crg_file(game_path) {
+        Ok(game_json) => game_json,
+        Err(e) => {
+            println!("Error loading game {}: {}", game_path.to_str().unwrap(), e);
+            exit(1);
+        }
+    };
+
+    for warning in game_json.import_warnings() {
+        println!("Warning loading game {}: {}", game_path.to_str().unwrap(), warning);
+    }
+
+    game_json
+}
+
+fn get_seed_game(seed_game_path: &Path) -> GameJson {
+    let seed_game = load_game(seed_game_path);
+
+    if seed_game.teams().len() < 2 {
+        println!("Error loading game {}: expected two teams", seed_game_path.to_str().unwrap());
+        exit(1);
+    }
+
+    seed_game
+}
+
+fn write_recorded_game(recorded_game: &RecordedGame, directory: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+    std::fs::write(directory.join("game.json"), recorded_game.game_json.to_string()).map_err(|e| e.to_string())?;
+    std::fs::write(directory.join("errors.yaml"), recorded_game.errors.to_string()).map_err(|e| e.to_string())?;
+    Statsbook::new(&recorded_game.game_json).write_csv(&directory.join("statsbook"))?;
+    Paperwork::new(&recorded_game.game_json).write_html(&directory.join("paperwork"))
+}
+
+/// Prints the diagnostics for a game and exits with an error if any rules were broken.
+fn validate_game(game_json: &GameJson) {
+    let validation = Validation::new(game_json);
+
+    if validation.diagnostics().is_empty() {
+        println!("No problems found");
     } else {
-        println!("{} - {:?}", official.name, official.role);
+        print!("{}", validation);
+    }
+
+    if validation.has_errors() {
+        exit(1);
     }
 }
 
 fn main() {
     let arguments = CommandLineArguments::parse();
 
-    let random = match arguments.random_seed {
+    if !(0.0..=1.0).contains(&arguments.error_rate) {
+        println!("Error rate must be between 0 and 1");
+        exit(1);
+    }
+
+    if let Some(Command::Validate { game_path: Some(game_path) }) = &arguments.command {
+        validate_game(&load_game(game_path));
+        return;
+    }
+
+    let mut random = match arguments.random_seed {
         None => {
             StdRng::from_entropy()
         },
         Some(seed) => {
-            let seed_bytes = u64::to_le_bytes(seed);
-            let mut seed_buffer = [0; 32];
-            seed_buffer[..seed_bytes.len()].copy_from_slice(&seed_bytes);
-        
-            StdRng::from_seed(seed_buffer)
+            derby_game_gen::seeded_random_source(seed)
         }
     };
 
-    let mut game = Game::random(random);
+    let seed_game = arguments.seed_game_path.as_deref().map(get_seed_game);
+
+    let (home_team, away_team, officials) = match &seed_game {
+        Some(seed_game) => {
+            let team = |side| seed_game.teams().iter().find(|(s, _)| *s == side).map(|(_, t)| t.clone()).unwrap();
+            let officials = match seed_game.officials() {
+                [] => Official::random_crew(&mut random),
+                officials => officials.to_vec(),
+            };
+
+            (team(TeamSide::Home), team(TeamSide::Away), officials)
+        },
+        None => (
+            get_team(&arguments.home_roster_path, &mut random),
+            get_team(&arguments.away_roster_path, &mut random),
+            get_officials(&arguments.officials_path, &mut random),
+        ),
+    };
+
+    let mut game = Game::new(random, TeamPair::new(home_team, away_team), officials);
+
+    game.verbose = arguments.verbose;
+
+    if let Some(penalty_code_weights) = arguments.penalty_code_weights {
+        game.penalty_code_weights = penalty_code_weights;
+    }
+
+    if let Some(event_date) = arguments.event_date.or(seed_game.map(|g| g.event_date)) {
+        game.game_json.event_date = event_date;
+    }
 
     println!("Home");
     println!("----");
-    print_team(&game.home_team.details);
+    print_team(&game.teams.home.details);
 
     println!();
     println!("Away");
     println!("----");
-    print_team(&game.away_team.details);
+    print_team(&game.teams.away.details);
 
     println!();
     println!("Officials");
     println!("---------");
     for o in game.officials.iter() {
-        print_official(&o);
+        print_official(o);
     }
 
     println!();
 
     game.run();
-    
+
+    println!();
+    println!("Result");
+    println!("------");
+    print_result(&game.game_json, &game.teams.home.details, &game.teams.away.details);
+    println!();
+
     if let Some(json_path) = arguments.json_output_path {
         match std::fs::write(&json_path, game.game_json.to_string()) {
             Ok(_) => {
@@ -97,4 +286,56 @@ fn main() {
         }
     }
 
+    if let Some(yaml_path) = arguments.yaml_output_path {
+        match std::fs::write(&yaml_path, game.game_events.to_string()) {
+            Ok(_) => {
+                println!("Events YAML written to {}", yaml_path.to_str().unwrap());
+            },
+            Err(e) => {
+                println!("Error writing events YAML: {}", e);
+            }
+        }
+    }
+
+    if let Some(statsbook_path) = arguments.statsbook_output_path {
+        match Statsbook::new(&game.game_json).write_csv(&statsbook_path) {
+            Ok(_) => {
+                println!("Statsbook written to {}", statsbook_path.to_str().unwrap());
+            },
+            Err(e) => {
+                println!("Error writing statsbook: {}", e);
+            }
+        }
+    }
+
+    if let Some(paperwork_path) = arguments.paperwork_output_path {
+        match Paperwork::new(&game.game_json).write_html(&paperwork_path) {
+            Ok(_) => {
+                println!("Paperwork written to {}", paperwork_path.to_str().unwrap());
+            },
+            Err(e) => {
+                println!("Error writing paperwork: {}", e);
+            }
+        }
+    }
+
+    if let Some(recorded_path) = arguments.recorded_output_path {
+        let recorded_game = game.record_with_errors(arguments.error_rate);
+
+        match write_recorded_game(&recorded_game, &recorded_path) {
+            Ok(_) => {
+                println!("Recorded game with {} errors written to {}", recorded_game.errors.errors().len(), recorded_path.to_str().unwrap());
+            },
+            Err(e) => {
+                println!("Error writing recorded game: {}", e);
+            }
+        }
+    }
+
+    if let Some(Command::Validate { game_path: None }) = arguments.command {
+        println!();
+        println!("Validation");
+        println!("----------");
+        validate_game(&game.game_json);
+    }
 }
@@ -1,9 +1,11 @@
+use std::{path::Path, str::FromStr};
+
 use rand::{Rng, rngs::StdRng};
 use uuid::Uuid;
 
-use crate::word_list;
+use crate::{crew_file::CrewFile, random_id, team_side::TeamSide, word_list};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum OfficialRole {
     PenaltyLineupTracker,
     PenaltyWrangler,
@@ -16,6 +18,45 @@ pub enum OfficialRole {
     InsidePackReferee,
     OutsidePackReferee,
     JammerReferee,
+    NonSkatingOfficialAlternate,
+    RefereeAlternate,
+}
+
+impl OfficialRole {
+    pub fn is_referee(&self) -> bool {
+        matches!(self, OfficialRole::InsidePackReferee | OfficialRole::OutsidePackReferee | OfficialRole::JammerReferee | OfficialRole::RefereeAlternate)
+    }
+
+    /// Whether officials in this role each cover a single team.
+    pub fn is_team_assigned(&self) -> bool {
+        matches!(self, OfficialRole::PenaltyLineupTracker | OfficialRole::Scorekeeper | OfficialRole::PenaltyBoxTimer | OfficialRole::JammerReferee)
+    }
+}
+
+impl FromStr for OfficialRole {
+    type Err = String;
+
+    /// Parses a role from its name in any case and spacing, e.g. "Jam Timer" or "jam_timer", or its usual abbreviation, e.g. "JT".
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let normalized: String = value.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase();
+
+        match normalized.as_str() {
+            "penaltylineuptracker" | "plt" | "penaltytracker" | "pt" | "lineuptracker" | "lt" => Ok(OfficialRole::PenaltyLineupTracker),
+            "penaltywrangler" | "pw" => Ok(OfficialRole::PenaltyWrangler),
+            "insidewhiteboard" | "insidewhiteboardoperator" | "iwb" => Ok(OfficialRole::InsideWhiteboard),
+            "jamtimer" | "jt" => Ok(OfficialRole::JamTimer),
+            "scorekeeper" | "sk" => Ok(OfficialRole::Scorekeeper),
+            "scoreboardoperator" | "sbo" | "so" => Ok(OfficialRole::ScoreboardOperator),
+            "penaltyboxmanager" | "pbm" => Ok(OfficialRole::PenaltyBoxManager),
+            "penaltyboxtimer" | "pbt" => Ok(OfficialRole::PenaltyBoxTimer),
+            "insidepackreferee" | "ipr" => Ok(OfficialRole::InsidePackReferee),
+            "outsidepackreferee" | "opr" => Ok(OfficialRole::OutsidePackReferee),
+            "jammerreferee" | "jr" => Ok(OfficialRole::JammerReferee),
+            "nonskatingofficialalternate" | "nsoalternate" | "altn" => Ok(OfficialRole::NonSkatingOfficialAlternate),
+            "refereealternate" | "altr" => Ok(OfficialRole::RefereeAlternate),
+            _ => Err(format!("Unknown official role '{}'", value)),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -24,38 +65,56 @@ pub struct Official {
     pub name: String,
     pub is_head: bool,
     pub role: OfficialRole,
+    pub league: Option<String>,
+    pub certification: Option<String>,
+    pub team: Option<TeamSide>,
 }
 
 impl Official {
+    /// Creates an official with a random name for the given role.
     pub fn random(random_source: &mut StdRng, role: OfficialRole, is_head: bool) -> Official {
         Official {
-            id: Uuid::new_v4(),
+            id: random_id::random_uuid(random_source),
             name: Self::get_random_name(random_source),
             is_head,
-            role
+            role,
+            league: None,
+            certification: None,
+            team: None,
         }
     }
 
+    pub fn with_team(mut self, team: TeamSide) -> Official {
+        self.team = Some(team);
+        self
+    }
+
+    /// Loads an officiating crew from a JSON or CSV crew file, see [`CrewFile`] for the formats.
+    pub fn crew_from_file(path: &Path, random_source: &mut StdRng) -> Result<Vec<Official>, String> {
+        CrewFile::read(path)?.into_crew(random_source)
+    }
+
+    /// Creates a full crew of referees and NSOs, with a head NSO and a head referee.
     pub fn random_crew(random_source: &mut StdRng) -> Vec<Official> {
         vec![
-            Self::random(random_source, OfficialRole::PenaltyLineupTracker, true),
-            Self::random(random_source, OfficialRole::PenaltyLineupTracker, false),
+            Self::random(random_source, OfficialRole::PenaltyLineupTracker, true).with_team(TeamSide::Home),
+            Self::random(random_source, OfficialRole::PenaltyLineupTracker, false).with_team(TeamSide::Away),
             Self::random(random_source, OfficialRole::PenaltyWrangler, false),
             Self::random(random_source, OfficialRole::InsideWhiteboard, false),
             Self::random(random_source, OfficialRole::JamTimer, false),
-            Self::random(random_source, OfficialRole::Scorekeeper, false),
-            Self::random(random_source, OfficialRole::Scorekeeper, false),
+            Self::random(random_source, OfficialRole::Scorekeeper, false).with_team(TeamSide::Home),
+            Self::random(random_source, OfficialRole::Scorekeeper, false).with_team(TeamSide::Away),
             Self::random(random_source, OfficialRole::ScoreboardOperator, false),
             Self::random(random_source, OfficialRole::PenaltyBoxManager, false),
-            Self::random(random_source, OfficialRole::PenaltyBoxTimer, false),
-            Self::random(random_source, OfficialRole::PenaltyBoxTimer, false),
+            Self::random(random_source, OfficialRole::PenaltyBoxTimer, false).with_team(TeamSide::Home),
+            Self::random(random_source, OfficialRole::PenaltyBoxTimer, false).with_team(TeamSide::Away),
             Self::random(random_source, OfficialRole::InsidePackReferee, true),
             Self::random(random_source, OfficialRole::InsidePackReferee, false),
             Self::random(random_source, OfficialRole::OutsidePackReferee, false),
             Self::random(random_source, OfficialRole::OutsidePackReferee, false),
             Self::random(random_source, OfficialRole::OutsidePackReferee, false),
-            Self::random(random_source, OfficialRole::JammerReferee, false),
-            Self::random(random_source, OfficialRole::JammerReferee, false),
+            Self::random(random_source, OfficialRole::JammerReferee, false).with_team(TeamSide::Home),
+            Self::random(random_source, OfficialRole::JammerReferee, false).with_team(TeamSide::Away),
         ]
     }
 
@@ -65,4 +124,27 @@ impl Official {
 
         first_name.to_owned() + " " + last_name
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OfficialRole;
+
+    #[test]
+    fn roles_parse_from_names_and_abbreviations() {
+        assert_eq!("Jam Timer".parse(), Ok(OfficialRole::JamTimer));
+        assert_eq!("jam_timer".parse(), Ok(OfficialRole::JamTimer));
+        assert_eq!("JT".parse(), Ok(OfficialRole::JamTimer));
+        assert_eq!("Penalty Tracker".parse(), Ok(OfficialRole::PenaltyLineupTracker));
+        assert_eq!("ScoreBoard Operator".parse(), Ok(OfficialRole::ScoreboardOperator));
+        assert_eq!("jr".parse(), Ok(OfficialRole::JammerReferee));
+        assert_eq!("Referee Alternate".parse(), Ok(OfficialRole::RefereeAlternate));
+        assert_eq!("ALTN".parse(), Ok(OfficialRole::NonSkatingOfficialAlternate));
+    }
+
+    #[test]
+    fn unknown_roles_are_rejected() {
+        assert!("Announcer".parse::<OfficialRole>().is_err());
+        assert!("".parse::<OfficialRole>().is_err());
+    }
+}
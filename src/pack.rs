@@ -0,0 +1,144 @@
+use crate::team_side::TeamSide;
+
+pub const TRACK_LENGTH: f32 = 200.0;
+pub const PACK_PROXIMITY: f32 = 10.0;
+pub const ENGAGEMENT_ZONE_LENGTH: f32 = 20.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pack {
+    pub rear: f32,
+    pub front: f32,
+}
+
+impl Pack {
+    pub fn find(blockers: &[(TeamSide, f32)]) -> Option<Pack> {
+        let mut blockers = blockers.to_vec();
+        blockers.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let mut groups: Vec<&[(TeamSide, f32)]> = Vec::new();
+        let mut group_start = 0;
+        for index in 1..=blockers.len() {
+            let is_group_end = index == blockers.len() || blockers[index].1 - blockers[index - 1].1 > PACK_PROXIMITY;
+            if is_group_end {
+                groups.push(&blockers[group_start..index]);
+                group_start = index;
+            }
+        }
+
+        let largest_size = groups.iter().map(|g| g.len()).max()?;
+        let mut largest_groups = groups.iter().filter(|g| g.len() == largest_size);
+
+        let group = largest_groups.next()?;
+        let has_both_teams = group.iter().any(|(t, _)| *t == TeamSide::Home) && group.iter().any(|(t, _)| *t == TeamSide::Away);
+
+        if largest_groups.next().is_some() || !has_both_teams {
+            return None;
+        }
+
+        Some(Pack {
+            rear: group.first().unwrap().1,
+            front: group.last().unwrap().1,
+        })
+    }
+
+    pub fn spanning(blockers: &[(TeamSide, f32)]) -> Pack {
+        let rear = blockers.iter().map(|(_, l)| *l).reduce(f32::min).unwrap_or(0.0);
+        let front = blockers.iter().map(|(_, l)| *l).reduce(f32::max).unwrap_or(0.0);
+
+        Pack { rear, front }
+    }
+
+    pub fn length(&self) -> f32 {
+        self.front - self.rear
+    }
+
+    pub fn center(&self) -> f32 {
+        (self.rear + self.front) / 2.0
+    }
+
+    /// Distance of a location ahead of the rear of the pack, wrapping around the track so that
+    /// skaters approaching the pack from behind are close to `TRACK_LENGTH`.
+    pub fn relative_location(&self, location: f32) -> f32 {
+        (location - self.rear).rem_euclid(TRACK_LENGTH)
+    }
+
+    pub fn contains(&self, location: f32) -> bool {
+        self.relative_location(location) <= self.length()
+    }
+
+    pub fn is_at_rear(&self, location: f32) -> bool {
+        self.relative_location(location) >= TRACK_LENGTH - PACK_PROXIMITY
+    }
+
+    pub fn is_approaching(&self, location: f32) -> bool {
+        self.relative_location(location) >= TRACK_LENGTH - ENGAGEMENT_ZONE_LENGTH
+    }
+
+    pub fn is_in_engagement_zone(&self, location: f32) -> bool {
+        self.is_approaching(location) || self.relative_location(location) <= self.length() + ENGAGEMENT_ZONE_LENGTH
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pack, TRACK_LENGTH};
+    use crate::team_side::TeamSide::{Away, Home};
+
+    #[test]
+    fn pack_is_largest_group_within_proximity() {
+        let blockers = [(Home, 10.0), (Away, 18.0), (Home, 25.0), (Away, 31.0), (Home, 60.0), (Away, 65.0)];
+
+        assert_eq!(Pack::find(&blockers), Some(Pack { rear: 10.0, front: 31.0 }));
+    }
+
+    #[test]
+    fn no_pack_when_largest_groups_are_tied() {
+        let blockers = [(Home, 10.0), (Away, 15.0), (Home, 60.0), (Away, 65.0)];
+
+        assert_eq!(Pack::find(&blockers), None);
+    }
+
+    #[test]
+    fn no_pack_when_largest_group_has_one_team() {
+        let blockers = [(Home, 10.0), (Home, 15.0), (Home, 20.0), (Away, 60.0), (Away, 65.0)];
+
+        assert_eq!(Pack::find(&blockers), None);
+    }
+
+    #[test]
+    fn no_pack_without_blockers() {
+        assert_eq!(Pack::find(&[]), None);
+    }
+
+    #[test]
+    fn spanning_pack_covers_every_blocker() {
+        let pack = Pack::spanning(&[(Home, 40.0), (Away, 5.0), (Home, 90.0)]);
+
+        assert_eq!(pack, Pack { rear: 5.0, front: 90.0 });
+        assert_eq!(pack.length(), 85.0);
+        assert_eq!(pack.center(), 47.5);
+    }
+
+    #[test]
+    fn locations_wrap_around_the_track() {
+        let pack = Pack { rear: 5.0, front: 15.0 };
+
+        assert_eq!(pack.relative_location(10.0), 5.0);
+        assert_eq!(pack.relative_location(0.0), TRACK_LENGTH - 5.0);
+        assert!(pack.contains(15.0));
+        assert!(!pack.contains(16.0));
+        assert!(pack.is_at_rear(0.0));
+        assert!(!pack.is_at_rear(TRACK_LENGTH - 20.0));
+    }
+
+    #[test]
+    fn engagement_zone_extends_in_front_of_and_behind_the_pack() {
+        let pack = Pack { rear: 50.0, front: 60.0 };
+
+        assert!(pack.is_in_engagement_zone(31.0));
+        assert!(pack.is_approaching(31.0));
+        assert!(pack.is_in_engagement_zone(80.0));
+        assert!(!pack.is_in_engagement_zone(81.0));
+        assert!(!pack.is_in_engagement_zone(29.0));
+    }
+}
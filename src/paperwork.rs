@@ -0,0 +1,171 @@
+use std::path::Path;
+
+use crate::{game_json::GameJson, statsbook::{Rows, SheetWriter}, team_side::TeamSide};
+
+const STYLE: &str = "body { font-family: sans-serif; font-size: 10pt; } \
+    section { page-break-after: always; } \
+    table { border-collapse: collapse; width: 100%; } \
+    th, td { border: 1px solid #000; padding: 2px 4px; text-align: center; } \
+    th { background: #ddd; } \
+    td.skater { font-weight: bold; } \
+    p.legend { font-size: 8pt; }";
+
+const LINEUP_LEGEND: &str = "/ entered the box, X entered and exited the box, S sat in the box between jams, \
+    $ sat in the box between jams and was released, 3 injured and sitting out the next three jams. \
+    I (injured without the jam being called) is not used, as every simulated injury calls the jam";
+
+#[derive(Clone, Copy)]
+enum SheetType {
+    Score,
+    Penalties,
+    Lineups,
+}
+
+impl SheetType {
+    fn file_name(&self) -> &'static str {
+        match self {
+            SheetType::Score => "score",
+            SheetType::Penalties => "penalties",
+            SheetType::Lineups => "lineups",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            SheetType::Score => "Score Sheet",
+            SheetType::Penalties => "Penalty Tracking Sheet",
+            SheetType::Lineups => "Lineup Sheet",
+        }
+    }
+}
+
+/// A printable sheet for one team as a standalone HTML page, with a page per period.
+pub struct PaperworkSheet {
+    pub name: String,
+    pub html: String,
+}
+
+/// Printable NSO paperwork for a simulated game: score, penalty tracking and lineup sheets for each team.
+///
+/// The sheets hold the same rows as the matching [`Statsbook`](crate::Statsbook) tabs.
+pub struct Paperwork {
+    sheets: Vec<PaperworkSheet>,
+}
+
+impl Paperwork {
+    pub fn new(game_json: &GameJson) -> Paperwork {
+        let writer = SheetWriter::new(game_json);
+        let mut sheets = Vec::new();
+
+        for team in [TeamSide::Home, TeamSide::Away] {
+            for sheet_type in [SheetType::Score, SheetType::Penalties, SheetType::Lineups] {
+                sheets.push(PaperworkSheet {
+                    name: format!("{}_{}", Self::team_label(team).to_lowercase(), sheet_type.file_name()),
+                    html: Self::render_sheet(game_json, &writer, team, sheet_type),
+                });
+            }
+        }
+
+        Paperwork { sheets }
+    }
+
+    pub fn sheets(&self) -> &[PaperworkSheet] {
+        &self.sheets
+    }
+
+    /// Writes each sheet to `<name>.html` in the given directory, creating the directory if needed.
+    pub fn write_html(&self, directory: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+
+        for sheet in self.sheets.iter() {
+            std::fs::write(directory.join(format!("{}.html", sheet.name)), &sheet.html).map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    fn team_label(team: TeamSide) -> &'static str {
+        match team {
+            TeamSide::Home => "Home",
+            TeamSide::Away => "Away",
+        }
+    }
+
+    fn render_sheet(game_json: &GameJson, writer: &SheetWriter, team: TeamSide, sheet_type: SheetType) -> String {
+        let team_name = writer.team(team).map(|t| t.name.clone()).unwrap_or_default();
+        let team_color = writer.team(team).map(|t| t.color.clone()).unwrap_or_default();
+        let title = format!("{} {} - {}", team_name, sheet_type.title(), game_json.event_date.format("%Y-%m-%d"));
+
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n",
+            escape(&title),
+            STYLE,
+        );
+
+        for period_number in 1..=game_json.periods().len() {
+            let rows = match sheet_type {
+                SheetType::Score => writer.score_team_rows(team, period_number),
+                SheetType::Penalties => writer.penalties_team_rows(team, period_number),
+                SheetType::Lineups => writer.lineups_team_rows(team, period_number),
+            };
+
+            html.push_str("<section>\n");
+            html.push_str(&format!("<h1>{}</h1>\n", escape(&title)));
+            html.push_str(&format!(
+                "<h2>Period {} - {} team ({})</h2>\n",
+                period_number,
+                Self::team_label(team),
+                escape(&team_color),
+            ));
+            html.push_str(&Self::render_table(&rows, sheet_type));
+
+            if let SheetType::Lineups = sheet_type {
+                html.push_str(&format!("<p class=\"legend\">{}</p>\n", escape(LINEUP_LEGEND)));
+            }
+
+            html.push_str("</section>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn render_table(rows: &Rows, sheet_type: SheetType) -> String {
+        let mut html = String::from("<table>\n");
+
+        if let Some(header) = rows.first() {
+            html.push_str("<tr>");
+            for cell in header {
+                html.push_str(&format!("<th>{}</th>", escape(cell)));
+            }
+            html.push_str("</tr>\n");
+        }
+
+        for (index, row) in rows.iter().skip(1).enumerate() {
+            html.push_str("<tr>");
+            for (column, cell) in row.iter().enumerate() {
+                let is_skater_number = match sheet_type {
+                    SheetType::Penalties => column == 0 && index % 2 == 0,
+                    _ => false,
+                };
+
+                if is_skater_number {
+                    html.push_str(&format!("<td class=\"skater\" rowspan=\"2\">{}</td>", escape(cell)));
+                } else if !(matches!(sheet_type, SheetType::Penalties) && column == 0) {
+                    html.push_str(&format!("<td>{}</td>", escape(cell)));
+                }
+            }
+            html.push_str("</tr>\n");
+        }
+
+        html.push_str("</table>\n");
+        html
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,133 @@
+use std::str::FromStr;
+
+use rand::{distributions::{Distribution, WeightedIndex}, rngs::StdRng};
+
+pub const CUT: &str = "X";
+pub const MISCONDUCT: &str = "G";
+pub const ILLEGAL_POSITION: &str = "P";
+pub const FOULED_OUT: &str = "FO";
+pub const EXPELLED: &str = "EXP";
+
+pub const PENALTY_CODES: &[&str] = &["A", "B", "C", "D", "E", "F", "G", "H", "I", "L", "M", "N", "P", "X"];
+
+/// Relative likelihood of each penalty code being issued, parsed from `CODE=WEIGHT` pairs.
+#[derive(Clone, Debug)]
+pub struct PenaltyCodeWeights {
+    weights: Vec<(String, f64)>,
+}
+
+impl Default for PenaltyCodeWeights {
+    fn default() -> Self {
+        PenaltyCodeWeights {
+            weights: vec![
+                ("A".to_string(), 11.0),
+                ("B".to_string(), 20.0),
+                ("C".to_string(), 8.0),
+                ("D".to_string(), 3.0),
+                ("E".to_string(), 1.0),
+                ("F".to_string(), 12.0),
+                ("G".to_string(), 1.0),
+                ("H".to_string(), 6.0),
+                ("I".to_string(), 3.0),
+                ("L".to_string(), 7.0),
+                ("M".to_string(), 5.0),
+                ("N".to_string(), 2.0),
+                ("P".to_string(), 8.0),
+                ("X".to_string(), 13.0),
+            ],
+        }
+    }
+}
+
+impl PenaltyCodeWeights {
+    pub fn random_code(&self, random_source: &mut StdRng) -> String {
+        let distribution = WeightedIndex::new(self.weights.iter().map(|(_, w)| w)).unwrap();
+
+        self.weights[distribution.sample(random_source)].0.clone()
+    }
+}
+
+impl FromStr for PenaltyCodeWeights {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut weights = PenaltyCodeWeights::default();
+
+        for entry in value.split(',').map(|e| e.trim()).filter(|e| !e.is_empty()) {
+            let (code, weight) = entry.split_once('=')
+                .ok_or_else(|| format!("Expected CODE=WEIGHT but found '{}'", entry))?;
+            let code = code.trim().to_uppercase();
+            let weight: f64 = weight.trim().parse()
+                .map_err(|_| format!("Invalid weight '{}' for penalty code {}", weight.trim(), code))?;
+
+            if !PENALTY_CODES.contains(&code.as_str()) {
+                return Err(format!("Unknown penalty code '{}'", code));
+            }
+
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(format!("Weight for penalty code {} must be zero or greater", code));
+            }
+
+            weights.weights.iter_mut().find(|(c, _)| *c == code).unwrap().1 = weight;
+        }
+
+        if weights.weights.iter().all(|(_, w)| *w == 0.0) {
+            return Err("At least one penalty code must have a weight greater than zero".to_string());
+        }
+
+        Ok(weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PenaltyCodeWeights, PENALTY_CODES};
+    use crate::seeded_random_source;
+
+    fn weight(weights: &PenaltyCodeWeights, code: &str) -> f64 {
+        weights.weights.iter().find(|(c, _)| c == code).unwrap().1
+    }
+
+    #[test]
+    fn parsed_weights_override_defaults() {
+        let weights: PenaltyCodeWeights = " b=2.5, x = 0 ,".parse().unwrap();
+
+        assert_eq!(weight(&weights, "B"), 2.5);
+        assert_eq!(weight(&weights, "X"), 0.0);
+        assert_eq!(weight(&weights, "A"), weight(&PenaltyCodeWeights::default(), "A"));
+    }
+
+    #[test]
+    fn empty_string_gives_default_weights() {
+        let weights: PenaltyCodeWeights = "".parse().unwrap();
+
+        for code in PENALTY_CODES {
+            assert_eq!(weight(&weights, code), weight(&PenaltyCodeWeights::default(), code));
+        }
+    }
+
+    #[test]
+    fn invalid_entries_are_rejected() {
+        for value in ["B", "B=heavy", "Z=1", "FO=1", "B=-1", "B=inf", "B=NaN"] {
+            assert!(value.parse::<PenaltyCodeWeights>().is_err(), "'{}' should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn all_zero_weights_are_rejected() {
+        let value = PENALTY_CODES.iter().map(|c| format!("{}=0", c)).collect::<Vec<String>>().join(",");
+
+        assert!(value.parse::<PenaltyCodeWeights>().is_err());
+    }
+
+    #[test]
+    fn random_code_only_returns_weighted_codes() {
+        let all_but_cut = PENALTY_CODES.iter().filter(|c| **c != "X").map(|c| format!("{}=0", c)).collect::<Vec<String>>().join(",");
+        let weights: PenaltyCodeWeights = all_but_cut.parse().unwrap();
+        let mut random_source = seeded_random_source(1);
+
+        for _ in 0..100 {
+            assert_eq!(weights.random_code(&mut random_source), "X");
+        }
+    }
+}
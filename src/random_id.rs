@@ -0,0 +1,6 @@
+use rand::{Rng, rngs::StdRng};
+use uuid::{Builder, Uuid};
+
+pub fn random_uuid(random_source: &mut StdRng) -> Uuid {
+    Builder::from_random_bytes(random_source.gen()).into_uuid()
+}
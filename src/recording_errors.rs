@@ -0,0 +1,295 @@
+use std::{collections::HashMap, fmt::Display};
+
+use rand::{Rng, rngs::StdRng};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{game_json::GameJson, rules::FOUL_OUT_PENALTY_COUNT, team_side::TeamSide};
+
+/// A mistake made by an official when recording the game.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "error")]
+pub enum RecordingErrorType {
+    MissedTripPoints {
+        team: TeamSide,
+        trip: usize,
+        actual_points: u8,
+        recorded_points: u8,
+    },
+    WrongPenaltySkater {
+        team: TeamSide,
+        code: String,
+        actual_number: String,
+        recorded_number: String,
+    },
+    MismarkedLead {
+        actual_lead: Option<TeamSide>,
+        recorded_lead: Option<TeamSide>,
+    },
+    BoxTripWrongJam {
+        team: TeamSide,
+        skater_number: String,
+        recorded_jam: usize,
+    },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RecordingError {
+    pub period: usize,
+    pub jam: usize,
+    #[serde(flatten)]
+    pub error_type: RecordingErrorType,
+}
+
+/// Every error injected into an as-recorded copy of a game, written as YAML.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RecordingErrors {
+    errors: Vec<RecordingError>,
+}
+
+impl RecordingErrors {
+    pub fn errors(&self) -> &[RecordingError] {
+        &self.errors
+    }
+}
+
+impl Display for RecordingErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", serde_yaml::to_string(self).unwrap())
+    }
+}
+
+/// A copy of a game as recorded by officials, alongside the errors that make it differ from the true game.
+pub struct RecordedGame {
+    pub game_json: GameJson,
+    pub errors: RecordingErrors,
+}
+
+impl RecordedGame {
+    /// Copies a game with each recorded trip score, lead, penalty and box trip having `error_rate` chance of being
+    /// recorded wrongly.
+    pub fn new(game_json: &GameJson, error_rate: f64, random_source: &mut StdRng) -> RecordedGame {
+        let mut recorded = RecordedGame {
+            game_json: game_json.clone(),
+            errors: RecordingErrors::default(),
+        };
+
+        recorded.miss_trip_points(error_rate, random_source);
+        recorded.mismark_leads(error_rate, random_source);
+        recorded.swap_penalty_skaters(error_rate, random_source);
+        recorded.move_box_trips(error_rate, random_source);
+
+        recorded.errors.errors.sort_by_key(|e| (e.period, e.jam));
+
+        recorded
+    }
+
+    fn skater_number(&self, team: TeamSide, skater_id: Uuid) -> String {
+        self.game_json.teams().iter()
+            .filter(|(side, _)| *side == team)
+            .flat_map(|(_, t)| t.roster.iter())
+            .find(|s| s.id == skater_id)
+            .map(|s| s.number.clone())
+            .unwrap_or_default()
+    }
+
+    fn miss_trip_points(&mut self, error_rate: f64, random_source: &mut StdRng) {
+        for (period_index, period) in self.game_json.periods_mut().iter_mut().enumerate() {
+            for (jam_index, jam) in period.jams_mut().iter_mut().enumerate() {
+                for (team, team_jam) in jam.team_jams.iter_mut() {
+                    for (trip_index, trip) in team_jam.trips_mut().iter_mut().enumerate() {
+                        if trip.score == 0 || !random_source.gen_bool(error_rate) {
+                            continue;
+                        }
+
+                        let recorded_points = random_source.gen_range(0..trip.score);
+
+                        self.errors.errors.push(RecordingError {
+                            period: period_index + 1,
+                            jam: jam_index + 1,
+                            error_type: RecordingErrorType::MissedTripPoints {
+                                team,
+                                trip: trip_index + 1,
+                                actual_points: trip.score,
+                                recorded_points,
+                            },
+                        });
+
+                        trip.score = recorded_points;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Marks lead for the wrong team, or for a team when neither jammer earned lead. Lost lead and the call are moved
+    /// with the lead so the recorded jam stays consistent.
+    fn mismark_leads(&mut self, error_rate: f64, random_source: &mut StdRng) {
+        for (period_index, period) in self.game_json.periods_mut().iter_mut().enumerate() {
+            for (jam_index, jam) in period.jams_mut().iter_mut().enumerate() {
+                if !random_source.gen_bool(error_rate) {
+                    continue;
+                }
+
+                let actual_lead = jam.team_jams.iter().find(|(_, t)| t.is_lead).map(|(team, _)| team);
+                let recorded_lead = match actual_lead {
+                    Some(team) => team.opponent(),
+                    None => if random_source.gen_bool(0.5) { TeamSide::Home } else { TeamSide::Away },
+                };
+
+                if let Some(team) = actual_lead {
+                    let (lost_lead, called_off) = (jam.team_jams[team].lost_lead, jam.team_jams[team].called_off);
+                    jam.team_jams[team].is_lead = false;
+                    jam.team_jams[team].lost_lead = false;
+                    jam.team_jams[team].called_off = false;
+                    jam.team_jams[recorded_lead].lost_lead = lost_lead;
+                    jam.team_jams[recorded_lead].called_off = called_off;
+                }
+                jam.team_jams[recorded_lead].is_lead = true;
+
+                self.errors.errors.push(RecordingError {
+                    period: period_index + 1,
+                    jam: jam_index + 1,
+                    error_type: RecordingErrorType::MismarkedLead { actual_lead, recorded_lead: Some(recorded_lead) },
+                });
+            }
+        }
+    }
+
+    /// Records penalties against a teammate with a different number to the penalized skater. Only teammates who stay
+    /// below the foul-out limit and were never removed are chosen, so the mistake doesn't also break foul-out rules.
+    fn swap_penalty_skaters(&mut self, error_rate: f64, random_source: &mut StdRng) {
+        let teams = self.game_json.teams().to_vec();
+        let removed_ids: Vec<Uuid> = self.game_json.removals().iter().map(|r| r.skater_id).collect();
+        let mut penalty_counts: HashMap<Uuid, usize> = HashMap::new();
+        for penalty in self.game_json.penalties() {
+            *penalty_counts.entry(penalty.skater_id).or_default() += 1;
+        }
+        let mut errors = Vec::new();
+
+        for penalty in self.game_json.penalties_mut().iter_mut() {
+            if !random_source.gen_bool(error_rate) {
+                continue;
+            }
+
+            let Some((_, team)) = teams.iter().find(|(side, _)| *side == penalty.team) else {
+                continue;
+            };
+            let Some(actual_skater) = team.roster.iter().find(|s| s.id == penalty.skater_id) else {
+                continue;
+            };
+            let teammates: Vec<_> = team.roster.iter()
+                .filter(|s| s.id != penalty.skater_id && !removed_ids.contains(&s.id))
+                .filter(|s| penalty_counts.get(&s.id).copied().unwrap_or(0) + 1 < FOUL_OUT_PENALTY_COUNT)
+                .collect();
+            if teammates.is_empty() {
+                continue;
+            }
+
+            let recorded_skater = teammates[random_source.gen_range(0..teammates.len())];
+
+            errors.push(RecordingError {
+                period: penalty.period_number,
+                jam: penalty.jam_number,
+                error_type: RecordingErrorType::WrongPenaltySkater {
+                    team: penalty.team,
+                    code: penalty.code.clone(),
+                    actual_number: actual_skater.number.clone(),
+                    recorded_number: recorded_skater.number.clone(),
+                },
+            });
+
+            *penalty_counts.entry(penalty.skater_id).or_default() -= 1;
+            *penalty_counts.entry(recorded_skater.id).or_default() += 1;
+            penalty.skater_id = recorded_skater.id;
+        }
+
+        self.errors.errors.extend(errors);
+    }
+
+    /// Logs the start of box trips in the jam before or after the one the skater was seated in.
+    fn move_box_trips(&mut self, error_rate: f64, random_source: &mut StdRng) {
+        let jam_counts: Vec<usize> = self.game_json.periods().iter().map(|p| p.jam_count()).collect();
+        let mut errors = Vec::new();
+
+        for box_trip_index in 0..self.game_json.box_trips().len() {
+            if !random_source.gen_bool(error_rate) {
+                continue;
+            }
+
+            let box_trip = &self.game_json.box_trips()[box_trip_index];
+            let jam_count = jam_counts[box_trip.start_period_number - 1];
+            let latest_jam = match box_trip.end_tick {
+                Some(_) if box_trip.end_period_number == box_trip.start_period_number => box_trip.end_jam_number - usize::from(box_trip.end_between_jams),
+                _ => jam_count,
+            };
+
+            let offsets = if random_source.gen_bool(0.5) { [1, -1] } else { [-1, 1] };
+            let Some(recorded_jam) = offsets.into_iter()
+                .map(|o| box_trip.start_jam_number as i64 + o)
+                .find(|j| *j >= 1 && *j <= latest_jam as i64)
+                .map(|j| j as usize) else {
+                continue;
+            };
+
+            errors.push(RecordingError {
+                period: box_trip.start_period_number,
+                jam: box_trip.start_jam_number,
+                error_type: RecordingErrorType::BoxTripWrongJam {
+                    team: box_trip.team,
+                    skater_number: self.skater_number(box_trip.team, box_trip.skater_id),
+                    recorded_jam,
+                },
+            });
+
+            self.game_json.box_trips_mut()[box_trip_index].start_jam_number = recorded_jam;
+        }
+
+        self.errors.errors.extend(errors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{seeded_random_source, validation::Rule, Game, Validation};
+
+    use super::RecordedGame;
+
+    fn simulated_game(seed: u64) -> Game {
+        let mut game = Game::random(seeded_random_source(seed));
+        game.run();
+        game
+    }
+
+    #[test]
+    fn zero_error_rate_records_the_game_exactly() {
+        let game = simulated_game(1);
+        let recorded = RecordedGame::new(&game.game_json, 0.0, &mut seeded_random_source(1));
+
+        assert!(recorded.errors.errors().is_empty());
+        assert_eq!(recorded.game_json.to_string(), game.game_json.to_string());
+    }
+
+    #[test]
+    fn mismarked_leads_keep_calloffs_with_the_recorded_lead() {
+        for seed in 1..=3 {
+            let recorded = simulated_game(seed).record_with_errors(1.0);
+            let rules: Vec<Rule> = Validation::new(&recorded.game_json).diagnostics().iter().map(|d| d.rule).collect();
+
+            assert!(!recorded.errors.errors().is_empty());
+            assert!(!rules.contains(&Rule::CallWithoutLead), "Seed {}: {:?}", seed, rules);
+            assert!(!rules.contains(&Rule::BothTeamsLead), "Seed {}: {:?}", seed, rules);
+        }
+    }
+
+    #[test]
+    fn swapped_penalties_do_not_cause_foul_out_errors() {
+        for seed in 1..=20 {
+            let recorded = simulated_game(seed).record_with_errors(0.5);
+            let rules: Vec<Rule> = Validation::new(&recorded.game_json).diagnostics().iter().map(|d| d.rule).collect();
+
+            assert!(!rules.contains(&Rule::PenaltyAfterRemoval), "Seed {}: {:?}", seed, rules);
+            assert!(!rules.contains(&Rule::MissingFoulOut), "Seed {}: {:?}", seed, rules);
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use std::{collections::HashSet, path::Path};
+
+use rand::rngs::StdRng;
+use serde::Deserialize;
+
+use crate::{random_id, skater::{Position, Skater}, team::Team};
+
+pub const MAX_ROSTER_SIZE: usize = 15;
+
+/// A team roster as read from a JSON or CSV file.
+///
+/// JSON files hold the team details and a `skaters` list. CSV files hold one skater per row with
+/// a header of `name,number,position` plus the optional `speed`, `penalty_chance`, `captain` and
+/// `alternate_captain` columns, and take the team name from the file name.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RosterFile {
+    pub name: String,
+    pub league: Option<String>,
+    pub color: Option<String>,
+    pub skaters: Vec<RosterSkater>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RosterSkater {
+    pub name: String,
+    pub number: String,
+    pub position: String,
+    /// Skating speed in feet per second, random if not given.
+    pub speed: Option<f32>,
+    /// Chance of committing a penalty each second on track, random if not given.
+    pub penalty_chance: Option<f64>,
+    #[serde(default)]
+    pub captain: bool,
+    #[serde(default)]
+    pub alternate_captain: bool,
+}
+
+impl RosterFile {
+    /// Reads a roster from a `.json` or `.csv` file.
+    pub fn read(path: &Path) -> Result<RosterFile, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+
+        match extension.as_str() {
+            "json" => serde_json::from_str(&contents).map_err(|e| e.to_string()),
+            "csv" => Ok(RosterFile {
+                name: path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().replace('_', " "),
+                league: None,
+                color: None,
+                skaters: Self::read_csv_skaters(&contents)?,
+            }),
+            _ => Err(format!("Unsupported roster file type '{}', expected .json or .csv", extension)),
+        }
+    }
+
+    fn read_csv_skaters(contents: &str) -> Result<Vec<RosterSkater>, String> {
+        csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(contents.as_bytes())
+            .deserialize()
+            .map(|r| r.map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    /// Checks the roster against the limits for a sanctioned game.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Team name must not be empty".to_string());
+        }
+
+        if self.skaters.len() > MAX_ROSTER_SIZE {
+            return Err(format!("Roster has {} skaters, at most {} allowed", self.skaters.len(), MAX_ROSTER_SIZE));
+        }
+
+        let mut numbers = HashSet::new();
+        for skater in self.skaters.iter() {
+            if skater.name.trim().is_empty() {
+                return Err(format!("Skater #{} has no name", skater.number));
+            }
+
+            if skater.number.trim().is_empty() {
+                return Err(format!("Skater {} has no number", skater.name));
+            }
+
+            if !numbers.insert(skater.number.as_str()) {
+                return Err(format!("Skater number {} is used more than once", skater.number));
+            }
+
+            Self::parse_position(&skater.position).map_err(|e| format!("Skater {}: {}", skater.name, e))?;
+
+            if skater.speed.is_some_and(|s| !s.is_finite() || s <= 0.0) {
+                return Err(format!("Skater {} must have a speed greater than zero", skater.name));
+            }
+
+            if skater.penalty_chance.is_some_and(|c| !(0.0..=1.0).contains(&c)) {
+                return Err(format!("Skater {} must have a penalty chance between 0 and 1", skater.name));
+            }
+        }
+
+        if self.skaters.iter().filter(|s| s.captain).count() > 1 {
+            return Err("Roster has more than one captain".to_string());
+        }
+
+        if self.skaters.iter().filter(|s| s.alternate_captain).count() > 1 {
+            return Err("Roster has more than one alternate captain".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Validates the roster and builds a team from it, randomly generating any details not given.
+    pub fn into_team(self, random_source: &mut StdRng) -> Result<Team, String> {
+        self.validate()?;
+
+        let mut roster: Vec<Skater> = self.skaters.iter().map(|s| {
+            let mut skater = Skater::random(random_source);
+            skater.name = s.name.trim().to_string();
+            skater.number = s.number.clone();
+            skater.favored_position = Self::parse_position(&s.position).unwrap();
+            skater.base_speed = s.speed.unwrap_or(skater.base_speed);
+            skater.penalty_chance = s.penalty_chance.unwrap_or(skater.penalty_chance);
+            skater.is_captain = s.captain;
+            skater.is_alternate_captain = s.alternate_captain;
+            skater
+        }).collect();
+
+        roster.sort_by(|a, b| a.number.cmp(&b.number));
+
+        Ok(Team {
+            id: random_id::random_uuid(random_source),
+            league: self.league.unwrap_or_else(|| self.name.clone()),
+            name: self.name,
+            roster,
+            color: self.color.unwrap_or_else(|| Team::get_random_color(random_source)),
+        })
+    }
+
+    fn parse_position(position: &str) -> Result<Position, String> {
+        match position.trim().to_lowercase().as_str() {
+            "jammer" => Ok(Position::Jammer),
+            "pivot" => Ok(Position::Pivot),
+            "blocker" => Ok(Position::Blocker),
+            _ => Err(format!("Unknown position '{}', expected jammer, pivot or blocker", position)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RosterFile, RosterSkater};
+    use crate::{seeded_random_source, skater::Position};
+
+    const CSV_ROSTER: &str = "name,number,position,speed,captain\n\
+        Skater One, 1, jammer, 20.5, true\n\
+        Skater Two, 22, Pivot, , false\n\
+        Skater Three, 333, blocker, , false\n\
+        Skater Four, 4444, blocker, , false\n\
+        Skater Five, 5, jammer, , false\n\
+        Skater Six, 6, blocker, , false\n\
+        Skater Seven, 7, blocker, , false\n\
+        Skater Eight, 8, pivot, , false\n";
+
+    fn roster() -> RosterFile {
+        RosterFile {
+            name: "Test Roller Derby".to_string(),
+            league: None,
+            color: None,
+            skaters: RosterFile::read_csv_skaters(CSV_ROSTER).unwrap(),
+        }
+    }
+
+    fn skater(number: &str) -> RosterSkater {
+        RosterSkater {
+            name: format!("Skater {}", number),
+            number: number.to_string(),
+            position: "blocker".to_string(),
+            speed: None,
+            penalty_chance: None,
+            captain: false,
+            alternate_captain: false,
+        }
+    }
+
+    #[test]
+    fn csv_skaters_are_read_with_optional_columns() {
+        let skaters = RosterFile::read_csv_skaters(CSV_ROSTER).unwrap();
+
+        assert_eq!(skaters.len(), 8);
+        assert_eq!(skaters[0].name, "Skater One");
+        assert_eq!(skaters[0].speed, Some(20.5));
+        assert!(skaters[0].captain);
+        assert_eq!(skaters[1].speed, None);
+        assert_eq!(skaters[1].penalty_chance, None);
+    }
+
+    #[test]
+    fn json_roster_is_read() {
+        let roster: RosterFile = serde_json::from_str(r#"{
+            "name": "Test Roller Derby",
+            "color": "Teal",
+            "skaters": [{ "name": "Skater One", "number": "1", "position": "jammer", "alternate_captain": true }]
+        }"#).unwrap();
+
+        assert_eq!(roster.color.as_deref(), Some("Teal"));
+        assert!(roster.skaters[0].alternate_captain);
+        assert!(!roster.skaters[0].captain);
+    }
+
+    #[test]
+    fn valid_roster_passes_validation() {
+        assert_eq!(roster().validate(), Ok(()));
+    }
+
+    #[test]
+    fn small_rosters_and_numbers_with_letters_are_accepted() {
+        let mut roster = roster();
+        roster.skaters.truncate(5);
+        roster.skaters[0].number = "00".to_string();
+        roster.skaters[1].number = "7a".to_string();
+
+        assert_eq!(roster.validate(), Ok(()));
+    }
+
+    #[test]
+    fn roster_without_a_team_name_is_rejected() {
+        let mut roster = roster();
+        roster.name = " ".to_string();
+
+        assert!(roster.validate().is_err());
+    }
+
+    #[test]
+    fn roster_with_more_than_fifteen_skaters_is_rejected() {
+        let mut roster = roster();
+        roster.skaters.extend((10..18).map(|n| skater(&n.to_string())));
+
+        assert!(roster.validate().is_err());
+    }
+
+    #[test]
+    fn skater_without_a_name_is_rejected() {
+        let mut roster = roster();
+        roster.skaters[0].name = String::new();
+
+        assert!(roster.validate().is_err());
+    }
+
+    #[test]
+    fn skater_without_a_number_is_rejected() {
+        let mut roster = roster();
+        roster.skaters[0].number = " ".to_string();
+
+        assert!(roster.validate().is_err());
+    }
+
+    #[test]
+    fn duplicate_skater_numbers_are_rejected() {
+        let mut roster = roster();
+        roster.skaters[1].number = "1".to_string();
+
+        assert!(roster.validate().is_err());
+    }
+
+    #[test]
+    fn unknown_position_is_rejected() {
+        let mut roster = roster();
+        roster.skaters[0].position = "goalie".to_string();
+
+        assert!(roster.validate().is_err());
+    }
+
+    #[test]
+    fn zero_speed_is_rejected() {
+        let mut roster = roster();
+        roster.skaters[0].speed = Some(0.0);
+
+        assert!(roster.validate().is_err());
+    }
+
+    #[test]
+    fn penalty_chance_above_one_is_rejected() {
+        let mut roster = roster();
+        roster.skaters[0].penalty_chance = Some(1.5);
+
+        assert!(roster.validate().is_err());
+    }
+
+    #[test]
+    fn second_captain_is_rejected() {
+        let mut roster = roster();
+        roster.skaters[1].captain = true;
+
+        assert!(roster.validate().is_err());
+    }
+
+    #[test]
+    fn second_alternate_captain_is_rejected() {
+        let mut roster = roster();
+        roster.skaters[1].alternate_captain = true;
+        roster.skaters[2].alternate_captain = true;
+
+        assert!(roster.validate().is_err());
+    }
+
+    #[test]
+    fn team_is_built_from_roster() {
+        let team = roster().into_team(&mut seeded_random_source(1)).unwrap();
+
+        assert_eq!(team.name, "Test Roller Derby");
+        assert_eq!(team.league, "Test Roller Derby");
+        assert_eq!(team.roster.len(), 8);
+        assert!(team.roster.windows(2).all(|s| s[0].number <= s[1].number));
+
+        let captain = team.roster.iter().find(|s| s.number == "1").unwrap();
+        assert!(captain.is_captain);
+        assert_eq!(captain.base_speed, 20.5);
+        assert_eq!(captain.favored_position, Position::Jammer);
+    }
+}
@@ -0,0 +1,20 @@
+//! Limits and durations set by the rules of flat track roller derby, shared by the simulation, exports and validation.
+
+pub const PERIOD_COUNT: usize = 2;
+pub const PERIOD_DURATION: u64 = 30 * 60 * 1000;
+pub const JAM_DURATION: u64 = 2 * 60 * 1000;
+pub const LINEUP_DURATION: u64 = 30 * 1000;
+pub const INTERVAL_DURATION: u64 = 15 * 60 * 1000;
+pub const PENALTY_SIT_DURATION: u64 = 30 * 1000;
+
+pub const TEAM_TIMEOUT_COUNT: usize = 3;
+pub const TEAM_TIMEOUT_DURATION: u64 = 60 * 1000;
+
+pub const FOUL_OUT_PENALTY_COUNT: usize = 7;
+/// Jams a skater must sit out after an injury that called off a jam.
+pub const INJURY_SIT_OUT_JAMS: usize = 3;
+
+/// One point for each opposing blocker passed.
+pub const MAX_BLOCKER_TRIP_POINTS: u8 = 4;
+/// One point for each opposing blocker passed, plus one for lapping the opposing jammer.
+pub const MAX_TRIP_POINTS: u8 = 5;
@@ -1,7 +1,7 @@
 use rand::{Rng, rngs::StdRng};
 use uuid::Uuid;
 
-use crate::word_list;
+use crate::{random_id, word_list};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Position {
@@ -18,17 +18,22 @@ pub struct Skater {
     pub favored_position: Position,
     pub base_speed: f32,
     pub penalty_chance: f64,
+    pub is_captain: bool,
+    pub is_alternate_captain: bool,
 }
 
 impl Skater {
+    /// Creates a skater with a random derby name and number.
     pub fn random(random_source: &mut StdRng) -> Skater {
         Skater {
-            id: Uuid::new_v4(),
+            id: random_id::random_uuid(random_source),
             name: Self::get_random_name(random_source),
             number: Self::get_random_number(random_source),
             favored_position: Self::get_random_position(random_source),
             base_speed: Self::get_random_speed(random_source),
             penalty_chance: Self::get_random_penalty_chance(random_source),
+            is_captain: false,
+            is_alternate_captain: false,
         }
     }
 
@@ -0,0 +1,544 @@
+use std::path::Path;
+
+use uuid::Uuid;
+
+use crate::{clock::format_clock, game_events::TimeoutType, game_json::{self, FieldingSkaterJson, GameJson, JamJson, TripJson}, penalty_code, team::Team, team_side::TeamSide};
+
+const TRIP_COLUMN_COUNT: usize = 9;
+const PENALTY_COLUMN_COUNT: usize = 9;
+const BOX_SYMBOL_COLUMN_COUNT: usize = 3;
+/// Marks a skater injured in the jam, who must sit out the next three jams. Every simulated injury calls the jam, so
+/// the `I` symbol for an injury without the jam being called is never produced.
+const INJURY_SYMBOL: &str = "3";
+
+const SCORE_HEADER: &[&str] = &["Jam", "Jammer's Number", "Lost", "Lead", "Call", "Inj", "NI", "Trip 2", "Trip 3", "Trip 4", "Trip 5", "Trip 6", "Trip 7", "Trip 8", "Trip 9", "Trip 10", "Jam Total", "Game Total"];
+const PENALTIES_HEADER: &[&str] = &["Skater #", "1", "2", "3", "4", "5", "6", "7", "8", "9", "FO/EXP"];
+const LINEUPS_HEADER: &[&str] = &["Jam", "No Pivot", "Jammer", "", "", "", "Pivot", "", "", "", "Blocker", "", "", "", "Blocker", "", "", "", "Blocker", "", "", ""];
+const BENCH_HEADER: &[&str] = &["After Jam", "Period Elapsed", "Timeout", "Retained"];
+const BOX_HEADER: &[&str] = &["Period", "Jam In", "Between Jams", "Skater #", "Penalties", "Period Out", "Jam Out", "Time Served"];
+
+pub(crate) type Rows = Vec<Vec<String>>;
+
+/// A single statsbook tab as a grid of cells.
+pub struct StatsbookSheet {
+    pub name: &'static str,
+    pub rows: Rows,
+}
+
+/// A statsbook-like export of the WFTDA statsbook tabs for a simulated game.
+///
+/// This is not the statsbook's exact cell layout or its xlsx file. Each tab holds the same information as the statsbook
+/// tab in a simpler grid, with a section per period where the home team's block is on the left and the away team's
+/// block is on the right, separated by an empty column. The `Bench` tab lists the team timeouts and official
+/// reviews called from each team's bench, and the `Box` tab lists each team's box trips in the order they started.
+pub struct Statsbook {
+    sheets: Vec<StatsbookSheet>,
+}
+
+impl Statsbook {
+    pub fn new(game_json: &GameJson) -> Statsbook {
+        let writer = SheetWriter::new(game_json);
+
+        Statsbook {
+            sheets: vec![
+                StatsbookSheet { name: "IGRF", rows: writer.igrf_rows() },
+                StatsbookSheet { name: "Score", rows: writer.score_rows() },
+                StatsbookSheet { name: "Penalties", rows: writer.penalties_rows() },
+                StatsbookSheet { name: "Lineups", rows: writer.lineups_rows() },
+                StatsbookSheet { name: "Bench", rows: writer.bench_rows() },
+                StatsbookSheet { name: "Box", rows: writer.box_rows() },
+            ],
+        }
+    }
+
+    pub fn sheets(&self) -> &[StatsbookSheet] {
+        &self.sheets
+    }
+
+    /// Writes each tab to `<name>.csv` in the given directory, creating the directory if needed.
+    pub fn write_csv(&self, directory: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+
+        for sheet in self.sheets.iter() {
+            let mut writer = csv::WriterBuilder::new()
+                .flexible(true)
+                .from_path(directory.join(format!("{}.csv", sheet.name)))
+                .map_err(|e| e.to_string())?;
+
+            for row in sheet.rows.iter() {
+                writer.write_record(row).map_err(|e| e.to_string())?;
+            }
+
+            writer.flush().map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds the rows of each statsbook tab, shared with the printable paperwork.
+pub(crate) struct SheetWriter<'a> {
+    game_json: &'a GameJson,
+}
+
+impl<'a> SheetWriter<'a> {
+    pub(crate) fn new(game_json: &'a GameJson) -> SheetWriter<'a> {
+        SheetWriter { game_json }
+    }
+
+    pub(crate) fn team(&self, team: TeamSide) -> Option<&Team> {
+        self.game_json.teams().iter().find(|(side, _)| *side == team).map(|(_, t)| t)
+    }
+
+    fn skater_number(&self, team: TeamSide, skater_id: Uuid) -> String {
+        self.team(team)
+            .and_then(|t| t.roster.iter().find(|s| s.id == skater_id))
+            .map(|s| s.number.clone())
+            .unwrap_or_default()
+    }
+
+    fn igrf_rows(&self) -> Rows {
+        let game_json = self.game_json;
+        let team_value = |f: &dyn Fn(&Team) -> String| -> Vec<String> {
+            [TeamSide::Home, TeamSide::Away].into_iter().map(|side| self.team(side).map(f).unwrap_or_default()).collect()
+        };
+
+        let mut rows: Rows = vec![
+            row(&["Venue Name", game_json::EVENT_VENUE]),
+            row(&["City", game_json::EVENT_CITY]),
+            row(&["State/Province", game_json::EVENT_STATE]),
+            row(&["Game #", game_json::EVENT_GAME_NUMBER]),
+            row(&["Tournament", ""]),
+            row(&["Host League", game_json::EVENT_HOST_LEAGUE]),
+            row(&["Date", &game_json.event_date.format("%Y-%m-%d").to_string()]),
+            row(&["Start Time", game_json::EVENT_START_TIME]),
+            Vec::new(),
+            row(&["", "Home", "Away"]),
+            prefixed("League", team_value(&|t| t.league.clone())),
+            prefixed("Team", team_value(&|t| if t.name == t.league { "".to_string() } else { t.name.clone() })),
+            prefixed("Color", team_value(&|t| t.color.clone())),
+            Vec::new(),
+        ];
+
+        let roster_rows = |side: TeamSide| -> Rows {
+            let mut roster_rows = vec![row(&["Skater #", "Skater Name", "Flags"])];
+            roster_rows.extend(self.team(side).into_iter().flat_map(|t| t.roster.iter()).map(|s| {
+                let flags = if s.is_captain { "C" } else if s.is_alternate_captain { "A" } else { "" };
+                row(&[&s.number, &s.name, flags])
+            }));
+            roster_rows
+        };
+        rows.extend(side_by_side(roster_rows(TeamSide::Home), roster_rows(TeamSide::Away), 3));
+        rows.push(Vec::new());
+
+        rows.push(row(&["Game Summary", "Home Points", "Home Penalties", "Away Points", "Away Penalties"]));
+        for (index, period) in game_json.periods().iter().enumerate() {
+            let period_number = index + 1;
+            let penalty_count = |team: TeamSide| game_json.penalties().iter().filter(|p| p.team == team && p.period_number == period_number).count();
+
+            rows.push(vec![
+                format!("Period {}", period_number),
+                period.team_score(TeamSide::Home).to_string(),
+                penalty_count(TeamSide::Home).to_string(),
+                period.team_score(TeamSide::Away).to_string(),
+                penalty_count(TeamSide::Away).to_string(),
+            ]);
+        }
+        let penalty_count = |team: TeamSide| game_json.penalties().iter().filter(|p| p.team == team).count();
+        rows.push(vec![
+            "Total".to_string(),
+            game_json.get_team_score(TeamSide::Home).to_string(),
+            penalty_count(TeamSide::Home).to_string(),
+            game_json.get_team_score(TeamSide::Away).to_string(),
+            penalty_count(TeamSide::Away).to_string(),
+        ]);
+        rows.push(Vec::new());
+
+        rows.push(row(&["Expulsions", "Team", "Skater #", "Period", "Jam"]));
+        for removal in game_json.removals().iter().filter(|r| r.code == penalty_code::EXPELLED) {
+            rows.push(vec![
+                "".to_string(),
+                self.team(removal.team).map(|t| t.name.clone()).unwrap_or_default(),
+                self.skater_number(removal.team, removal.skater_id),
+                removal.period_number.to_string(),
+                removal.jam_number.to_string(),
+            ]);
+        }
+        rows.push(Vec::new());
+
+        rows.push(row(&["Officials", "Role", "Name", "League", "Cert", "Team"]));
+        let mut officials: Vec<_> = game_json.officials().iter().collect();
+        officials.sort_by_key(|o| o.role.is_referee());
+        for official in officials {
+            let team = match official.team {
+                Some(TeamSide::Home) => "Home",
+                Some(TeamSide::Away) => "Away",
+                None => "",
+            };
+            let role = if official.is_head {
+                if official.role.is_referee() { "Head Referee" } else { "Head NSO" }
+            } else {
+                GameJson::format_official_role(official.role)
+            };
+
+            rows.push(row(&[
+                "",
+                role,
+                &official.name,
+                official.league.as_deref().unwrap_or_default(),
+                official.certification.as_deref().unwrap_or_default(),
+                team,
+            ]));
+        }
+
+        rows
+    }
+
+    fn score_rows(&self) -> Rows {
+        self.period_sections(SCORE_HEADER.len(), |team, period_number| self.score_team_rows(team, period_number))
+    }
+
+    /// A team's score sheet rows for a period, with game totals carried over from earlier periods.
+    pub(crate) fn score_team_rows(&self, team: TeamSide, period_number: usize) -> Rows {
+        let periods = self.game_json.periods();
+        let mut game_total: u32 = periods.iter().take(period_number - 1).map(|p| p.team_score(team)).sum();
+        let mut rows = vec![row(SCORE_HEADER)];
+
+        for (jam_index, jam) in periods[period_number - 1].jams().iter().enumerate() {
+            rows.extend(Self::score_jam_rows(jam, team, jam_index + 1, &mut game_total));
+        }
+
+        rows
+    }
+
+    fn score_jam_rows(jam: &JamJson, team: TeamSide, jam_number: usize, game_total: &mut u32) -> Rows {
+        let team_jam = jam.team_jam(team);
+        let has_star_passed = team_jam.star_pass_tick.is_some();
+        let opponent_has_star_passed = jam.team_jam(team.opponent()).star_pass_tick.is_some();
+        let is_checked = |value: bool| if value { "X" } else { "" }.to_string();
+
+        let mut score_row = |jam_label: String, jammer: Option<String>, flags: [bool; 5], trips: Vec<&TripJson>| -> Vec<String> {
+            let total: u32 = trips.iter().map(|t| t.score as u32).sum();
+            *game_total += total;
+
+            let mut cells = vec![jam_label, jammer.unwrap_or_default()];
+            cells.extend(flags.map(is_checked));
+            cells.extend(Self::trip_cells(&trips));
+            cells.push(total.to_string());
+            cells.push(game_total.to_string());
+            cells
+        };
+
+        let trips_before_star_pass: Vec<_> = team_jam.trips().iter().skip(1).filter(|t| !t.after_star_pass).collect();
+        let has_no_initial = !team_jam.trips().iter().any(|t| !t.after_star_pass && t.is_complete);
+        let jammer_row = score_row(
+            jam_number.to_string(),
+            team_jam.fielding().jammer.as_ref().map(|s| s.number.clone()),
+            [team_jam.lost_lead, team_jam.is_lead, team_jam.called_off && !has_star_passed, team_jam.injury, has_no_initial],
+            trips_before_star_pass,
+        );
+
+        let mut rows = vec![jammer_row];
+
+        if has_star_passed {
+            let trips_after_star_pass: Vec<_> = team_jam.trips().iter().skip(1).filter(|t| t.after_star_pass).collect();
+            let has_no_initial = team_jam.trips().first().is_some_and(|t| t.after_star_pass && !t.is_complete);
+
+            rows.push(score_row(
+                "SP".to_string(),
+                team_jam.fielding().pivot.as_ref().map(|s| s.number.clone()),
+                [false, false, team_jam.called_off, false, has_no_initial],
+                trips_after_star_pass,
+            ));
+        } else if opponent_has_star_passed {
+            rows.push(score_row("SP*".to_string(), None, [false; 5], Vec::new()));
+        }
+
+        rows
+    }
+
+    /// Scores for each trip after the initial trip, with any trips beyond the last column combined into it.
+    fn trip_cells(trips: &[&TripJson]) -> Vec<String> {
+        let mut cells: Vec<String> = trips.iter()
+            .map(|t| if t.is_complete || t.score > 0 { t.score.to_string() } else { "".to_string() })
+            .collect();
+
+        if cells.len() > TRIP_COLUMN_COUNT {
+            let overflow = cells.split_off(TRIP_COLUMN_COUNT - 1);
+            cells.push(overflow.join("+"));
+        }
+
+        cells.resize(TRIP_COLUMN_COUNT, "".to_string());
+        cells
+    }
+
+    fn penalties_rows(&self) -> Rows {
+        self.period_sections(PENALTIES_HEADER.len(), |team, period_number| self.penalties_team_rows(team, period_number))
+    }
+
+    /// A team's penalty rows for a period, with a row of penalty codes and a row of jam numbers for each skater.
+    pub(crate) fn penalties_team_rows(&self, team: TeamSide, period_number: usize) -> Rows {
+        let mut rows = vec![row(PENALTIES_HEADER)];
+
+        for skater in self.team(team).into_iter().flat_map(|t| t.roster.iter()) {
+            let penalties = self.game_json.penalties().iter().filter(|p| p.skater_id == skater.id && p.period_number == period_number);
+            let removal = self.game_json.removals().iter().find(|r| r.skater_id == skater.id && r.period_number == period_number);
+
+            let mut codes = vec![skater.number.clone()];
+            let mut jams = vec!["".to_string()];
+            for penalty in penalties {
+                codes.push(penalty.code.clone());
+                jams.push(penalty.jam_number.to_string());
+            }
+            codes.resize(PENALTY_COLUMN_COUNT + 1, "".to_string());
+            jams.resize(PENALTY_COLUMN_COUNT + 1, "".to_string());
+
+            match removal {
+                Some(removal) => {
+                    codes.push(self.removal_code(removal.skater_id, &removal.code, removal.tick));
+                    jams.push(removal.jam_number.to_string());
+                },
+                None => {
+                    codes.push("".to_string());
+                    jams.push("".to_string());
+                },
+            }
+
+            rows.push(codes);
+            rows.push(jams);
+        }
+
+        rows
+    }
+
+    /// Fouled out skaters are marked `FO`, while expelled skaters are marked with the code of the expulsion penalty.
+    fn removal_code(&self, skater_id: Uuid, code: &str, tick: u64) -> String {
+        if code == penalty_code::FOULED_OUT {
+            return code.to_string();
+        }
+
+        self.game_json.penalties().iter()
+            .rfind(|p| p.skater_id == skater_id && p.tick <= tick)
+            .map(|p| p.code.clone())
+            .unwrap_or_else(|| code.to_string())
+    }
+
+    fn lineups_rows(&self) -> Rows {
+        self.period_sections(LINEUPS_HEADER.len(), |team, period_number| self.lineups_team_rows(team, period_number))
+    }
+
+    pub(crate) fn lineups_team_rows(&self, team: TeamSide, period_number: usize) -> Rows {
+        let mut rows = vec![row(LINEUPS_HEADER)];
+
+        for (jam_index, jam) in self.game_json.periods()[period_number - 1].jams().iter().enumerate() {
+            rows.extend(self.lineups_jam_rows(jam, team, period_number, jam_index + 1));
+        }
+
+        rows
+    }
+
+    fn lineups_jam_rows(&self, jam: &JamJson, team: TeamSide, period_number: usize, jam_number: usize) -> Rows {
+        let team_jam = jam.team_jam(team);
+        let fielding = team_jam.fielding();
+        let has_star_passed = team_jam.star_pass_tick.is_some();
+
+        let skater_cells = |skater: &Option<FieldingSkaterJson>, after_star_pass: bool| -> Vec<String> {
+            let Some(skater) = skater else {
+                return vec!["".to_string(); BOX_SYMBOL_COLUMN_COUNT + 1];
+            };
+
+            let (before, after) = self.game_json.get_box_trip_symbols(team, period_number, jam_number, skater.skater_id);
+            let mut symbols: Vec<String> = if after_star_pass { after } else { before }.into_iter().map(|s| s.to_string()).collect();
+            if team_jam.injured_skater_id == Some(skater.skater_id) && after_star_pass == has_star_passed {
+                symbols.push(INJURY_SYMBOL.to_string());
+            }
+            symbols.resize(BOX_SYMBOL_COLUMN_COUNT, "".to_string());
+
+            let mut cells = vec![skater.number.clone()];
+            cells.extend(symbols);
+            cells
+        };
+
+        let no_pivot = if fielding.pivot.is_none() { "X" } else { "" };
+        let blockers = [&fielding.blocker1, &fielding.blocker2, &fielding.blocker3];
+
+        let mut jammer_row = row(&[&jam_number.to_string(), no_pivot]);
+        jammer_row.extend(skater_cells(&fielding.jammer, false));
+        jammer_row.extend(skater_cells(&fielding.pivot, false));
+        jammer_row.extend(blockers.iter().flat_map(|b| skater_cells(b, false)));
+
+        let mut rows = vec![jammer_row];
+
+        if has_star_passed {
+            let mut star_pass_row = row(&["SP", no_pivot]);
+            star_pass_row.extend(skater_cells(&fielding.pivot, true));
+            star_pass_row.extend(skater_cells(&fielding.jammer, true));
+            star_pass_row.extend(blockers.iter().flat_map(|b| skater_cells(b, true)));
+            rows.push(star_pass_row);
+        } else if jam.team_jam(team.opponent()).star_pass_tick.is_some() {
+            rows.push(row(&["SP*"]));
+        }
+
+        rows
+    }
+
+    fn bench_rows(&self) -> Rows {
+        self.period_sections(BENCH_HEADER.len(), |team, period_number| self.bench_team_rows(team, period_number))
+    }
+
+    /// A team's timeouts and official reviews for a period, in the order they were called.
+    fn bench_team_rows(&self, team: TeamSide, period_number: usize) -> Rows {
+        let mut rows = vec![row(BENCH_HEADER)];
+
+        for timeout in self.game_json.timeouts().iter().filter(|t| t.team == Some(team) && t.period_number == period_number) {
+            let timeout_type = match timeout.timeout_type {
+                TimeoutType::Team => "Team Timeout",
+                TimeoutType::Review => "Official Review",
+                TimeoutType::Official => "Official Timeout",
+            };
+            let is_retained = timeout.timeout_type == TimeoutType::Review && timeout.review_retained;
+
+            rows.push(vec![
+                timeout.preceding_jam_number.to_string(),
+                format_clock(timeout.period_clock_elapsed),
+                timeout_type.to_string(),
+                if is_retained { "X" } else { "" }.to_string(),
+            ]);
+        }
+
+        rows
+    }
+
+    /// A section for each period with the home team's rows on the left and the away team's on the right.
+    fn period_sections(&self, width: usize, team_rows: impl Fn(TeamSide, usize) -> Rows) -> Rows {
+        let mut rows = Rows::new();
+
+        for period_number in 1..=self.game_json.periods().len() {
+            rows.push(vec![format!("Period {}", period_number)]);
+            rows.extend(side_by_side(team_rows(TeamSide::Home, period_number), team_rows(TeamSide::Away, period_number), width));
+            rows.push(Vec::new());
+        }
+
+        rows
+    }
+
+    /// Time the jam clock was running between two ticks, which excludes lineups, timeouts and intervals.
+    fn jam_clock_time(&self, start_tick: u64, end_tick: u64) -> u64 {
+        self.game_json.periods().iter()
+            .flat_map(|p| p.jams())
+            .map(|j| end_tick.min(j.end_tick).saturating_sub(start_tick.max(j.start_tick)))
+            .sum()
+    }
+
+    fn box_rows(&self) -> Rows {
+        let [home_rows, away_rows] = [TeamSide::Home, TeamSide::Away].map(|team| {
+            let mut rows = vec![row(BOX_HEADER)];
+
+            for box_trip in self.game_json.box_trips().iter().filter(|t| t.team == team) {
+                let codes: Vec<&str> = self.game_json.penalties().iter()
+                    .filter(|p| p.box_trip_id == Some(box_trip.id()))
+                    .map(|p| p.code.as_str())
+                    .collect();
+                let time_served = box_trip.end_tick.map(|t| format_clock(self.jam_clock_time(box_trip.start_tick, t))).unwrap_or_default();
+                let (period_out, jam_out) = match box_trip.end_tick {
+                    Some(_) => (box_trip.end_period_number.to_string(), box_trip.end_jam_number.to_string()),
+                    None => ("".to_string(), "".to_string()),
+                };
+
+                rows.push(vec![
+                    box_trip.start_period_number.to_string(),
+                    box_trip.start_jam_number.to_string(),
+                    if box_trip.start_between_jams { "X" } else { "" }.to_string(),
+                    self.skater_number(team, box_trip.skater_id),
+                    codes.join(" "),
+                    period_out,
+                    jam_out,
+                    time_served,
+                ]);
+            }
+
+            rows
+        });
+
+        side_by_side(home_rows, away_rows, BOX_HEADER.len())
+    }
+}
+
+fn row(cells: &[&str]) -> Vec<String> {
+    cells.iter().map(|c| c.to_string()).collect()
+}
+
+fn prefixed(label: &str, cells: Vec<String>) -> Vec<String> {
+    let mut row = vec![label.to_string()];
+    row.extend(cells);
+    row
+}
+
+/// Places the away team's rows to the right of the home team's, padding the home block to `width` columns and
+/// leaving an empty column between them.
+fn side_by_side(home_rows: Rows, away_rows: Rows, width: usize) -> Rows {
+    let row_count = home_rows.len().max(away_rows.len());
+    let mut home_rows = home_rows.into_iter();
+    let mut away_rows = away_rows.into_iter();
+
+    (0..row_count).map(|_| {
+        let mut cells = home_rows.next().unwrap_or_default();
+        cells.resize(width + 1, "".to_string());
+        cells.extend(away_rows.next().unwrap_or_default());
+        cells
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{game_json::{FieldingSkaterJson, GameJson, TeamJamFielding}, seeded_random_source, team_side::TeamSide, Team, TeamPair};
+
+    use super::Statsbook;
+
+    #[test]
+    fn statsbook_has_every_tab() {
+        let game_json = GameJson::new(&mut seeded_random_source(1));
+        let names: Vec<&str> = Statsbook::new(&game_json).sheets().iter().map(|s| s.name).collect();
+
+        assert_eq!(names, vec!["IGRF", "Score", "Penalties", "Lineups", "Bench", "Box"]);
+    }
+
+    #[test]
+    fn time_served_only_counts_jam_clock_time() {
+        let mut random_source = seeded_random_source(1);
+        let mut game_json = GameJson::new(&mut random_source);
+        let team = Team::random(&mut random_source);
+        let skater = &team.roster[0];
+        let fielding = TeamJamFielding {
+            jammer: None,
+            pivot: None,
+            blocker1: Some(FieldingSkaterJson { skater_id: skater.id, number: skater.number.clone() }),
+            blocker2: None,
+            blocker3: None,
+        };
+        let fieldings = TeamPair::new(fielding.clone(), fielding);
+        game_json.add_team(TeamSide::Home, &team);
+        game_json.add_team(TeamSide::Away, &team);
+        game_json.add_period(0);
+
+        // Two one minute jams with a thirty second lineup between, and a box trip covering the last fifteen seconds of
+        // the first and the first fifteen seconds of the second.
+        for start_tick in [0, 90 * 1000] {
+            let id = game_json.new_id();
+            let period = game_json.current_period_mut().unwrap();
+            period.add_jam(id, start_tick, start_tick, &fieldings);
+            period.current_jam_mut().unwrap().end_tick = start_tick + 60 * 1000;
+
+            if start_tick == 0 {
+                game_json.add_penalty(TeamSide::Home, skater.id, "B", 45 * 1000);
+                game_json.start_box_trip(TeamSide::Home, skater.id, 45 * 1000, false);
+            }
+        }
+        game_json.end_box_trip(skater.id, 105 * 1000, false);
+
+        let statsbook = Statsbook::new(&game_json);
+        let box_sheet = statsbook.sheets().iter().find(|s| s.name == "Box").unwrap();
+
+        assert_eq!(box_sheet.rows[1][7], "0:30");
+    }
+}
@@ -1,26 +1,38 @@
+use std::path::Path;
+
 use rand::{Rng, rngs::StdRng};
 use uuid::Uuid;
 
-use crate::{skater::Skater, word_list};
+use crate::{random_id, roster_file::RosterFile, skater::Skater, word_list};
 
 #[derive(Clone)]
 pub struct Team {
     pub id: Uuid,
     pub name: String,
+    pub league: String,
     pub roster: Vec<Skater>,
     pub color: String,
 }
 
 impl Team {
+    /// Creates a team with a random name, color and roster of skaters.
     pub fn random(random_source: &mut StdRng) -> Team {
+        let name = Self::get_random_name(random_source);
+
         Team {
-            id: Uuid::new_v4(),
-            name: Self::get_random_name(random_source),
+            id: random_id::random_uuid(random_source),
+            league: name.clone(),
+            name,
             roster: Self::get_random_roster(random_source),
             color: Self::get_random_color(random_source),
         }
     }
 
+    /// Loads a team from a JSON or CSV roster file, see [`RosterFile`] for the formats.
+    pub fn from_roster_file(path: &Path, random_source: &mut StdRng) -> Result<Team, String> {
+        RosterFile::read(path)?.into_team(random_source)
+    }
+
     fn get_random_name(random_source: &mut StdRng) -> String {
         word_list::PLACE_NAMES[random_source.gen_range(0..word_list::PLACE_NAMES.len())].to_owned() + " Roller Derby"
     }
@@ -42,10 +54,15 @@ impl Team {
 
         roster.sort_by(|a, b| a.number.cmp(&b.number));
 
+        let captain_index = random_source.gen_range(0..roster.len());
+        let alternate_captain_index = (captain_index + random_source.gen_range(1..roster.len())) % roster.len();
+        roster[captain_index].is_captain = true;
+        roster[alternate_captain_index].is_alternate_captain = true;
+
         roster
     }
 
-    fn get_random_color(random_source: &mut StdRng) -> String {
+    pub(crate) fn get_random_color(random_source: &mut StdRng) -> String {
         word_list::COLORS[random_source.gen_range(0..word_list::COLORS.len())].to_string()
     }
 }
\ No newline at end of file
@@ -0,0 +1,59 @@
+use std::ops::{Index, IndexMut};
+
+use crate::team_side::TeamSide;
+
+/// A value held for each of the two teams in a game, indexed by [`TeamSide`].
+#[derive(Clone, Debug, Default)]
+pub struct TeamPair<T> {
+    pub home: T,
+    pub away: T,
+}
+
+impl<T> TeamPair<T> {
+    pub fn new(home: T, away: T) -> TeamPair<T> {
+        TeamPair { home, away }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (TeamSide, &T)> {
+        [(TeamSide::Home, &self.home), (TeamSide::Away, &self.away)].into_iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (TeamSide, &mut T)> {
+        [(TeamSide::Home, &mut self.home), (TeamSide::Away, &mut self.away)].into_iter()
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(TeamSide, &T) -> U) -> TeamPair<U> {
+        TeamPair {
+            home: f(TeamSide::Home, &self.home),
+            away: f(TeamSide::Away, &self.away),
+        }
+    }
+
+    /// Mutable access to a team's value alongside its opponent's.
+    pub fn with_opponent_mut(&mut self, team: TeamSide) -> (&mut T, &mut T) {
+        match team {
+            TeamSide::Home => (&mut self.home, &mut self.away),
+            TeamSide::Away => (&mut self.away, &mut self.home),
+        }
+    }
+}
+
+impl<T> Index<TeamSide> for TeamPair<T> {
+    type Output = T;
+
+    fn index(&self, team: TeamSide) -> &T {
+        match team {
+            TeamSide::Home => &self.home,
+            TeamSide::Away => &self.away,
+        }
+    }
+}
+
+impl<T> IndexMut<TeamSide> for TeamPair<T> {
+    fn index_mut(&mut self, team: TeamSide) -> &mut T {
+        match team {
+            TeamSide::Home => &mut self.home,
+            TeamSide::Away => &mut self.away,
+        }
+    }
+}
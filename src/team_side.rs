@@ -0,0 +1,23 @@
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum TeamSide {
+    Home,
+    Away,
+}
+
+impl TeamSide {
+    pub fn number(&self) -> u8 {
+        match self {
+            TeamSide::Home => 1,
+            TeamSide::Away => 2,
+        }
+    }
+
+    pub fn opponent(&self) -> TeamSide {
+        match self {
+            TeamSide::Home => TeamSide::Away,
+            TeamSide::Away => TeamSide::Home,
+        }
+    }
+}
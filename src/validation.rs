@@ -0,0 +1,523 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{clock::format_clock, game_json::{GameJson, JamJson, PenaltyJson}, rules::{FOUL_OUT_PENALTY_COUNT, INJURY_SIT_OUT_JAMS, JAM_DURATION, MAX_BLOCKER_TRIP_POINTS, MAX_TRIP_POINTS, PERIOD_DURATION}, team::Team, team_side::TeamSide};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum Rule {
+    NoJammer,
+    SkaterFieldedTwice,
+    UnknownSkater,
+    SkaterInBoxNotFielded,
+    FieldedAfterRemoval,
+    FieldedWhileInjured,
+    TripPointsAboveFour,
+    TripPointsAboveMaximum,
+    JamTooLong,
+    JamAfterPeriodEnd,
+    PeriodClockOverrun,
+    BothTeamsLead,
+    CallWithoutLead,
+    BoxTripWithoutPenalty,
+    PenaltyAfterRemoval,
+    MissingFoulOut,
+}
+
+/// A rule or consistency violation found in a game.
+#[derive(Clone, Debug, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub rule: Rule,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jam: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team: Option<TeamSide>,
+    pub message: String,
+}
+
+/// The diagnostics from checking a finished game against the rules, written as YAML.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Validation {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validation {
+    /// Checks a finished game, either simulated or imported from CRG.
+    pub fn new(game_json: &GameJson) -> Validation {
+        let mut validation = Validation::default();
+
+        for (period_index, period) in game_json.periods().iter().enumerate() {
+            for (jam_index, jam) in period.jams().iter().enumerate() {
+                validation.check_jam(game_json, jam, period_index + 1, jam_index + 1);
+            }
+        }
+
+        validation.check_penalties(game_json);
+        validation.check_box_trips(game_json);
+
+        validation.diagnostics.sort_by_key(|d| (d.period, d.jam));
+
+        validation
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.severity == Severity::Error)
+    }
+
+    fn add(&mut self, severity: Severity, rule: Rule, period: Option<usize>, jam: Option<usize>, team: Option<TeamSide>, message: String) {
+        self.diagnostics.push(Diagnostic { severity, rule, period, jam, team, message });
+    }
+
+    fn check_jam(&mut self, game_json: &GameJson, jam: &JamJson, period_number: usize, jam_number: usize) {
+        let (period, jam_position) = (Some(period_number), Some(jam_number));
+        let error = |validation: &mut Validation, rule: Rule, team: Option<TeamSide>, message: String| {
+            validation.add(Severity::Error, rule, period, jam_position, team, message);
+        };
+
+        let duration = jam.end_tick.saturating_sub(jam.start_tick);
+        if duration > JAM_DURATION {
+            error(self, Rule::JamTooLong, None, format!("Jam lasted {} but jams last at most {}", format_clock(duration), format_clock(JAM_DURATION)));
+        }
+
+        if !jam.is_overtime && jam.period_clock_elapsed_start >= PERIOD_DURATION {
+            error(self, Rule::JamAfterPeriodEnd, None, format!("Jam started at {} on the period clock, after the period ended", format_clock(jam.period_clock_elapsed_start)));
+        }
+
+        if jam.period_clock_elapsed_end > PERIOD_DURATION || jam.period_clock_elapsed_end < jam.period_clock_elapsed_start {
+            error(self, Rule::PeriodClockOverrun, None, format!(
+                "Period clock ran from {} to {} during the jam",
+                format_clock(jam.period_clock_elapsed_start),
+                format_clock(jam.period_clock_elapsed_end),
+            ));
+        }
+
+        if jam.team_jams.iter().all(|(_, t)| t.is_lead) {
+            error(self, Rule::BothTeamsLead, None, "Both jammers were marked as lead".to_string());
+        }
+
+        for (team, team_jam) in jam.team_jams.iter() {
+            let Some(team_details) = Self::team(game_json, team) else {
+                continue;
+            };
+            let fielding = team_jam.fielding();
+            let fielded: Vec<Uuid> = [&fielding.jammer, &fielding.pivot, &fielding.blocker1, &fielding.blocker2, &fielding.blocker3].into_iter()
+                .filter_map(|s| s.as_ref().map(|s| s.skater_id))
+                .collect();
+
+            if fielding.jammer.is_none() {
+                error(self, Rule::NoJammer, Some(team), "No jammer was fielded".to_string());
+            }
+
+            if team_jam.called_off && !team_jam.is_lead {
+                error(self, Rule::CallWithoutLead, Some(team), "Jam was called off by a jammer without lead".to_string());
+            }
+
+            for (index, skater_id) in fielded.iter().enumerate() {
+                let number = Self::skater_number(team_details, *skater_id);
+
+                if fielded[..index].contains(skater_id) {
+                    error(self, Rule::SkaterFieldedTwice, Some(team), format!("Skater {} was fielded in more than one position", number));
+                }
+
+                if !team_details.roster.iter().any(|s| s.id == *skater_id) {
+                    error(self, Rule::UnknownSkater, Some(team), format!("Skater {} is not on the team's roster", number));
+                }
+
+                let removal = game_json.removals().iter().find(|r| r.skater_id == *skater_id);
+                if removal.is_some_and(|r| (r.period_number, r.jam_number) < (period_number, jam_number)) {
+                    error(self, Rule::FieldedAfterRemoval, Some(team), format!("Skater {} was fielded after fouling out or being expelled", number));
+                }
+
+                if Self::was_injured_recently(game_json, team, *skater_id, period_number, jam_number) {
+                    error(self, Rule::FieldedWhileInjured, Some(team), format!("Skater {} was fielded within {} jams of being injured", number, INJURY_SIT_OUT_JAMS));
+                }
+            }
+
+            let box_skaters = game_json.box_trips().iter()
+                .filter(|t| t.team == team && t.covers_jam(period_number, jam_number) && !t.started_in_jam(period_number, jam_number))
+                .map(|t| t.skater_id);
+            for skater_id in box_skaters {
+                if !fielded.contains(&skater_id) {
+                    error(self, Rule::SkaterInBoxNotFielded, Some(team), format!("Skater {} was in the box from an earlier jam but was not fielded", Self::skater_number(team_details, skater_id)));
+                }
+            }
+
+            for (trip_index, trip) in team_jam.trips().iter().enumerate() {
+                if trip.score > MAX_TRIP_POINTS {
+                    error(self, Rule::TripPointsAboveMaximum, Some(team), format!("Trip {} scored {} points but at most {} can be scored", trip_index + 1, trip.score, MAX_TRIP_POINTS));
+                } else if trip.score > MAX_BLOCKER_TRIP_POINTS {
+                    self.add(Severity::Warning, Rule::TripPointsAboveFour, period, jam_position, Some(team), format!(
+                        "Trip {} scored {} points, which needs the opposing jammer to have been lapped",
+                        trip_index + 1,
+                        trip.score,
+                    ));
+                }
+            }
+        }
+    }
+
+    fn check_penalties(&mut self, game_json: &GameJson) {
+        for removal in game_json.removals() {
+            let removal_time = (removal.period_number, removal.jam_number, removal.tick);
+            let later_penalties = game_json.penalties().iter()
+                .filter(|p| p.skater_id == removal.skater_id && (p.period_number, p.jam_number, p.tick) > removal_time);
+
+            for penalty in later_penalties {
+                let number = Self::team(game_json, penalty.team).map(|t| Self::skater_number(t, penalty.skater_id)).unwrap_or_default();
+
+                self.add(Severity::Error, Rule::PenaltyAfterRemoval, Some(penalty.period_number), Some(penalty.jam_number), Some(penalty.team), format!(
+                    "Skater {} was given a {} penalty after fouling out or being expelled in period {} jam {}",
+                    number,
+                    penalty.code,
+                    removal.period_number,
+                    removal.jam_number,
+                ));
+            }
+        }
+
+        for (team, team_details) in game_json.teams() {
+            for skater in team_details.roster.iter() {
+                let penalties: Vec<&PenaltyJson> = game_json.penalties().iter().filter(|p| p.skater_id == skater.id).collect();
+                let has_been_removed = game_json.removals().iter().any(|r| r.skater_id == skater.id);
+
+                if penalties.len() >= FOUL_OUT_PENALTY_COUNT && !has_been_removed {
+                    let last_penalty = penalties.last().unwrap();
+
+                    self.add(Severity::Error, Rule::MissingFoulOut, Some(last_penalty.period_number), Some(last_penalty.jam_number), Some(*team), format!(
+                        "Skater {} has {} penalties but did not foul out",
+                        skater.number,
+                        penalties.len(),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn check_box_trips(&mut self, game_json: &GameJson) {
+        for box_trip in game_json.box_trips() {
+            let has_penalty = game_json.penalties().iter().any(|p| p.box_trip_id == Some(box_trip.id()));
+
+            if !has_penalty {
+                let number = Self::team(game_json, box_trip.team).map(|t| Self::skater_number(t, box_trip.skater_id)).unwrap_or_default();
+
+                self.add(Severity::Error, Rule::BoxTripWithoutPenalty, Some(box_trip.start_period_number), Some(box_trip.start_jam_number), Some(box_trip.team), format!(
+                    "Skater {} was in the box without a penalty",
+                    number,
+                ));
+            }
+        }
+    }
+
+    fn team(game_json: &GameJson, team: TeamSide) -> Option<&Team> {
+        game_json.teams().iter().find(|(side, _)| *side == team).map(|(_, t)| t)
+    }
+
+    fn skater_number(team: &Team, skater_id: Uuid) -> String {
+        team.roster.iter()
+            .find(|s| s.id == skater_id)
+            .map(|s| format!("#{}", s.number))
+            .unwrap_or_else(|| skater_id.as_hyphenated().to_string())
+    }
+
+    /// Whether the skater was injured in one of the team's previous few jams in the same period.
+    fn was_injured_recently(game_json: &GameJson, team: TeamSide, skater_id: Uuid, period_number: usize, jam_number: usize) -> bool {
+        let jams = game_json.periods()[period_number - 1].jams();
+        let first_jam = jam_number.saturating_sub(INJURY_SIT_OUT_JAMS).max(1);
+
+        (first_jam..jam_number).any(|j| jams[j - 1].team_jam(team).injured_skater_id == Some(skater_id))
+    }
+}
+
+impl Display for Validation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", serde_yaml::to_string(self).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::{game_json::{FieldingSkaterJson, GameJson, TeamJamFielding}, penalty_code, rules::{JAM_DURATION, PERIOD_DURATION}, seeded_random_source, team_side::TeamSide, Game, Team, TeamPair};
+
+    use super::{Rule, Validation};
+
+    const JAM_SPACING: u64 = 90 * 1000;
+    const TEST_JAM_DURATION: u64 = 60 * 1000;
+
+    /// A game with a single period of the given number of jams, each fielding the same skaters and won by the home
+    /// jammer, which breaks no rules.
+    fn game(jam_count: usize) -> GameJson {
+        let mut random_source = seeded_random_source(1);
+        let mut game_json = GameJson::new(&mut random_source);
+        game_json.add_team(TeamSide::Home, &Team::random(&mut random_source));
+        game_json.add_team(TeamSide::Away, &Team::random(&mut random_source));
+        game_json.add_period(0);
+
+        for _ in 0..jam_count {
+            add_unchanged_jam(&mut game_json);
+        }
+
+        game_json
+    }
+
+    fn skater_id(game_json: &GameJson, team: TeamSide, index: usize) -> Uuid {
+        game_json.teams().iter().find(|(side, _)| *side == team).unwrap().1.roster[index].id
+    }
+
+    fn fielding_skater(game_json: &GameJson, team: TeamSide, index: usize) -> Option<FieldingSkaterJson> {
+        let skater = &game_json.teams().iter().find(|(side, _)| *side == team).unwrap().1.roster[index];
+
+        Some(FieldingSkaterJson { skater_id: skater.id, number: skater.number.clone() })
+    }
+
+    fn fieldings(game_json: &GameJson) -> TeamPair<TeamJamFielding> {
+        let fielding = |team| TeamJamFielding {
+            jammer: fielding_skater(game_json, team, 0),
+            pivot: fielding_skater(game_json, team, 1),
+            blocker1: fielding_skater(game_json, team, 2),
+            blocker2: fielding_skater(game_json, team, 3),
+            blocker3: fielding_skater(game_json, team, 4),
+        };
+
+        TeamPair::new(fielding(TeamSide::Home), fielding(TeamSide::Away))
+    }
+
+    fn add_jam(game_json: &mut GameJson, fieldings: &TeamPair<TeamJamFielding>) {
+        let id = game_json.new_id();
+        let period = game_json.current_period_mut().unwrap();
+        let start_tick = period.jam_count() as u64 * JAM_SPACING;
+
+        period.add_jam(id, start_tick, start_tick, fieldings);
+
+        let jam = period.current_jam_mut().unwrap();
+        jam.end_tick = start_tick + TEST_JAM_DURATION;
+        jam.period_clock_elapsed_end = start_tick + TEST_JAM_DURATION;
+        jam.team_jams[TeamSide::Home].is_lead = true;
+    }
+
+    fn add_unchanged_jam(game_json: &mut GameJson) {
+        let fieldings = fieldings(game_json);
+
+        add_jam(game_json, &fieldings);
+    }
+
+    /// The tick part way through the most recent jam.
+    fn mid_jam_tick(game_json: &GameJson) -> u64 {
+        game_json.periods().last().unwrap().jams().last().unwrap().start_tick + TEST_JAM_DURATION / 2
+    }
+
+    fn rules(game_json: &GameJson) -> Vec<Rule> {
+        Validation::new(game_json).diagnostics().iter().map(|d| d.rule).collect()
+    }
+
+    #[test]
+    fn valid_game_has_no_diagnostics() {
+        assert!(rules(&game(3)).is_empty());
+    }
+
+    #[test]
+    fn simulated_games_have_no_errors() {
+        for seed in 1..=3 {
+            let mut game = Game::random(seeded_random_source(seed));
+            game.run();
+
+            let validation = Validation::new(&game.game_json);
+            assert!(!validation.has_errors(), "Seed {}: {}", seed, validation);
+        }
+    }
+
+    #[test]
+    fn team_without_jammer_is_an_error() {
+        let mut game_json = game(0);
+        let mut fieldings = fieldings(&game_json);
+        fieldings.home.jammer = None;
+        add_jam(&mut game_json, &fieldings);
+
+        assert_eq!(rules(&game_json), vec![Rule::NoJammer]);
+    }
+
+    #[test]
+    fn skater_in_two_positions_is_an_error() {
+        let mut game_json = game(0);
+        let mut fieldings = fieldings(&game_json);
+        fieldings.away.blocker3 = fieldings.away.blocker2.clone();
+        add_jam(&mut game_json, &fieldings);
+
+        assert_eq!(rules(&game_json), vec![Rule::SkaterFieldedTwice]);
+    }
+
+    #[test]
+    fn skater_not_on_roster_is_an_error() {
+        let mut game_json = game(0);
+        let mut fieldings = fieldings(&game_json);
+        fieldings.home.blocker1 = Some(FieldingSkaterJson { skater_id: Uuid::from_u128(1), number: "0".to_string() });
+        add_jam(&mut game_json, &fieldings);
+
+        assert_eq!(rules(&game_json), vec![Rule::UnknownSkater]);
+    }
+
+    #[test]
+    fn skater_in_box_from_earlier_jam_must_be_fielded() {
+        let mut game_json = game(1);
+        let skater_id = skater_id(&game_json, TeamSide::Home, 2);
+        let tick = mid_jam_tick(&game_json);
+        game_json.add_penalty(TeamSide::Home, skater_id, "B", tick);
+        game_json.start_box_trip(TeamSide::Home, skater_id, tick, false);
+
+        let mut fieldings = fieldings(&game_json);
+        fieldings.home.blocker1 = fielding_skater(&game_json, TeamSide::Home, 5);
+        add_jam(&mut game_json, &fieldings);
+
+        assert_eq!(rules(&game_json), vec![Rule::SkaterInBoxNotFielded]);
+    }
+
+    #[test]
+    fn removed_skater_cannot_be_fielded_in_later_jams() {
+        let mut game_json = game(1);
+        let tick = mid_jam_tick(&game_json);
+        game_json.add_removal(TeamSide::Away, skater_id(&game_json, TeamSide::Away, 3), penalty_code::EXPELLED, tick);
+
+        assert!(rules(&game_json).is_empty());
+
+        add_unchanged_jam(&mut game_json);
+
+        assert_eq!(rules(&game_json), vec![Rule::FieldedAfterRemoval]);
+    }
+
+    #[test]
+    fn injured_skater_cannot_be_fielded_in_following_jams() {
+        let mut game_json = game(1);
+        let skater_id = skater_id(&game_json, TeamSide::Home, 0);
+        let team_jam = &mut game_json.current_period_mut().unwrap().current_jam_mut().unwrap().team_jams[TeamSide::Home];
+        team_jam.injury = true;
+        team_jam.injured_skater_id = Some(skater_id);
+        add_unchanged_jam(&mut game_json);
+
+        assert_eq!(rules(&game_json), vec![Rule::FieldedWhileInjured]);
+    }
+
+    #[test]
+    fn trip_points_above_four_are_a_warning_and_above_five_an_error() {
+        for (score, expected_rules, is_error) in [(4, vec![], false), (5, vec![Rule::TripPointsAboveFour], false), (6, vec![Rule::TripPointsAboveMaximum], true)] {
+            let mut game_json = game(1);
+            let id = game_json.new_id();
+            let tick = mid_jam_tick(&game_json);
+            let team_jam = &mut game_json.current_period_mut().unwrap().current_jam_mut().unwrap().team_jams[TeamSide::Away];
+            team_jam.add_trip(id, tick);
+            team_jam.current_trip_mut().unwrap().score = score;
+
+            assert_eq!(rules(&game_json), expected_rules);
+            assert_eq!(Validation::new(&game_json).has_errors(), is_error);
+        }
+    }
+
+    #[test]
+    fn jam_longer_than_jam_clock_is_an_error() {
+        let mut game_json = game(1);
+        let jam = game_json.current_period_mut().unwrap().current_jam_mut().unwrap();
+        jam.end_tick = jam.start_tick + JAM_DURATION + 1000;
+
+        assert_eq!(rules(&game_json), vec![Rule::JamTooLong]);
+    }
+
+    #[test]
+    fn jam_starting_after_period_end_is_an_error_unless_overtime() {
+        let mut game_json = game(1);
+        let jam = game_json.current_period_mut().unwrap().current_jam_mut().unwrap();
+        jam.period_clock_elapsed_start = PERIOD_DURATION;
+        jam.period_clock_elapsed_end = PERIOD_DURATION;
+
+        assert_eq!(rules(&game_json), vec![Rule::JamAfterPeriodEnd]);
+
+        game_json.current_period_mut().unwrap().current_jam_mut().unwrap().is_overtime = true;
+
+        assert!(rules(&game_json).is_empty());
+    }
+
+    #[test]
+    fn period_clock_running_past_period_end_is_an_error() {
+        let mut game_json = game(1);
+        game_json.current_period_mut().unwrap().current_jam_mut().unwrap().period_clock_elapsed_end = PERIOD_DURATION + 1000;
+
+        assert_eq!(rules(&game_json), vec![Rule::PeriodClockOverrun]);
+    }
+
+    #[test]
+    fn both_jammers_with_lead_is_an_error() {
+        let mut game_json = game(1);
+        game_json.current_period_mut().unwrap().current_jam_mut().unwrap().team_jams[TeamSide::Away].is_lead = true;
+
+        assert_eq!(rules(&game_json), vec![Rule::BothTeamsLead]);
+    }
+
+    #[test]
+    fn calling_off_without_lead_is_an_error() {
+        let mut game_json = game(1);
+        let team_jams = &mut game_json.current_period_mut().unwrap().current_jam_mut().unwrap().team_jams;
+        team_jams[TeamSide::Home].called_off = true;
+
+        assert!(rules(&game_json).is_empty());
+
+        game_json.current_period_mut().unwrap().current_jam_mut().unwrap().team_jams[TeamSide::Away].called_off = true;
+
+        assert_eq!(rules(&game_json), vec![Rule::CallWithoutLead]);
+    }
+
+    #[test]
+    fn box_trip_without_penalty_is_an_error() {
+        let mut game_json = game(1);
+        let skater_id = skater_id(&game_json, TeamSide::Away, 2);
+        let tick = mid_jam_tick(&game_json);
+        game_json.start_box_trip(TeamSide::Away, skater_id, tick, false);
+        game_json.end_box_trip(skater_id, tick + 10 * 1000, false);
+
+        assert_eq!(rules(&game_json), vec![Rule::BoxTripWithoutPenalty]);
+    }
+
+    #[test]
+    fn penalty_after_removal_in_the_same_jam_is_an_error() {
+        let mut game_json = game(1);
+        let skater_id = skater_id(&game_json, TeamSide::Home, 2);
+        let tick = mid_jam_tick(&game_json);
+        game_json.add_penalty(TeamSide::Home, skater_id, penalty_code::MISCONDUCT, tick);
+        game_json.add_removal(TeamSide::Home, skater_id, penalty_code::EXPELLED, tick);
+
+        assert!(rules(&game_json).is_empty());
+
+        game_json.add_penalty(TeamSide::Home, skater_id, penalty_code::MISCONDUCT, tick + 1000);
+
+        assert_eq!(rules(&game_json), vec![Rule::PenaltyAfterRemoval]);
+    }
+
+    #[test]
+    fn seventh_penalty_without_foul_out_is_an_error() {
+        let mut game_json = game(1);
+        let skater_id = skater_id(&game_json, TeamSide::Away, 1);
+        let tick = mid_jam_tick(&game_json);
+
+        for penalty_number in 0..7 {
+            game_json.add_penalty(TeamSide::Away, skater_id, "C", tick + penalty_number);
+        }
+
+        assert_eq!(rules(&game_json), vec![Rule::MissingFoulOut]);
+
+        game_json.add_removal(TeamSide::Away, skater_id, penalty_code::FOULED_OUT, tick + 6);
+
+        assert!(rules(&game_json).is_empty());
+    }
+}